@@ -0,0 +1,194 @@
+//! String tables of `ctru::i18n`
+//!
+//! This crate holds the [string table](table) parser used by `ctru::i18n`. It only depends on `std`, so that
+//! the build script of an application can check its tables with [`check_tables`], and fail the build when one
+//! of them is invalid or lacks some strings.
+//!
+//! # Example
+//!
+//! In the `main` function of `build.rs`, with `ctru-i18n-check` in the `[build-dependencies]`:
+//!
+//! ```no_run
+//! println!("cargo:rerun-if-changed=romfs/lang");
+//!
+//! if let Err(errors) = ctru_i18n_check::check_tables("romfs/lang") {
+//!     for error in &errors {
+//!         println!("cargo:warning={error}");
+//!     }
+//!     panic!("{} invalid string table(s)", errors.len());
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub mod table;
+
+use table::{ParseError, StringTable};
+
+/// Extension of the string table files.
+pub const TABLE_EXTENSION: &str = "lang";
+
+/// Error found by [`check_tables`]
+#[derive(Debug)]
+pub enum TableError {
+    /// The file or directory couldn't be read.
+    Read { path: PathBuf, error: io::Error },
+    /// The table isn't valid.
+    Parse { path: PathBuf, error: ParseError },
+    /// The table lacks some keys (without their plural suffix) found in the other tables.
+    MissingKeys { path: PathBuf, keys: Vec<String> },
+}
+
+/// Checks the string tables (the `.lang` files) of `dir`.
+///
+/// Every table must be valid, and hold every key found in the other tables. All the errors are returned,
+/// sorted by path.
+pub fn check_tables<P: AsRef<Path>>(dir: P) -> Result<(), Vec<TableError>> {
+    let dir = dir.as_ref();
+    let entries = std::fs::read_dir(dir).map_err(|error| {
+        vec![TableError::Read {
+            path: dir.to_owned(),
+            error,
+        }]
+    })?;
+
+    let mut paths = Vec::new();
+    let mut errors = Vec::new();
+    for entry in entries {
+        match entry {
+            Ok(entry) => paths.push(entry.path()),
+            Err(error) => errors.push(TableError::Read {
+                path: dir.to_owned(),
+                error,
+            }),
+        }
+    }
+    paths.retain(|path| path.extension().map_or(false, |ext| ext == TABLE_EXTENSION));
+    paths.sort_unstable();
+
+    let mut tables = Vec::new();
+    for path in paths {
+        match std::fs::read_to_string(&path) {
+            Ok(text) => match StringTable::parse(&text) {
+                Ok(table) => tables.push((path, table)),
+                Err(error) => errors.push(TableError::Parse { path, error }),
+            },
+            Err(error) => errors.push(TableError::Read { path, error }),
+        }
+    }
+
+    for (path, table) in &tables {
+        let mut keys = tables
+            .iter()
+            .flat_map(|(_, other)| table.missing_keys(other))
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+
+        if !keys.is_empty() {
+            errors.push(TableError::MissingKeys {
+                path: path.clone(),
+                keys,
+            });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Read { path, error } => write!(f, "could not read {}: {error}", path.display()),
+            Self::Parse { path, error } => {
+                write!(f, "invalid string table {}: {error}", path.display())
+            }
+            Self::MissingKeys { path, keys } => write!(
+                f,
+                "string table {} is missing: {}",
+                path.display(),
+                keys.join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for TableError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Read { error, .. } => Some(error),
+            Self::Parse { error, .. } => Some(error),
+            Self::MissingKeys { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the tables to a new directory in the temporary directory.
+    fn table_dir(name: &str, tables: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ctru-i18n-check-{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, text) in tables {
+            std::fs::write(dir.join(file), text).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn example_tables() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../ctru-rs/examples/romfs/lang");
+        check_tables(dir).unwrap();
+    }
+
+    #[test]
+    fn missing_keys() {
+        let dir = table_dir(
+            "missing",
+            &[
+                (
+                    "en.lang",
+                    "greeting = Hello\napples.one = apple\napples.other = apples",
+                ),
+                ("fr.lang", "apples.other = pommes"),
+                ("notes.txt", "not a table"),
+            ],
+        );
+
+        let errors = check_tables(&dir).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        match &errors[0] {
+            TableError::MissingKeys { path, keys } => {
+                assert_eq!(path, &dir.join("fr.lang"));
+                assert_eq!(keys, &["greeting"]);
+            }
+            error => panic!("unexpected error: {error}"),
+        }
+    }
+
+    #[test]
+    fn invalid_table() {
+        let dir = table_dir("invalid", &[("en.lang", "greeting")]);
+
+        let errors = check_tables(&dir).unwrap_err();
+        assert!(matches!(
+            &errors[..],
+            [TableError::Parse {
+                error: ParseError::MissingSeparator { line: 1 },
+                ..
+            }]
+        ));
+        assert!(check_tables(dir.join("missing")).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! String tables
+//!
+//! A string table is a text file with one `key = value` entry per line. Empty lines and lines starting
+//! with `#` are ignored, and `\n` and `\\` can be used in values for a line break and a backslash.
+//!
+//! ```text
+//! # Greeting shown on the title screen
+//! greeting = Hello, {name}!
+//! apples.one = {count} apple
+//! apples.other = {count} apples
+//! ```
+//!
+//! Keys ending with a plural category (`.zero`, `.one`, `.two`, `.few`, `.many` or `.other`) are the
+//! plural forms of the same string.
+
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+
+/// Suffixes of the plural forms of a key.
+pub const PLURAL_SUFFIXES: [&str; 6] = [".zero", ".one", ".two", ".few", ".many", ".other"];
+
+/// The strings of a language
+#[derive(Clone, Debug, Default)]
+pub struct StringTable {
+    entries: HashMap<String, String>,
+}
+
+/// Error type for the parsing of a string table
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    /// The line (starting from 1) isn't a comment and has no `=`.
+    MissingSeparator { line: usize },
+    /// The line (starting from 1) has an empty key.
+    EmptyKey { line: usize },
+    /// The key was already defined on an earlier line.
+    DuplicateKey { line: usize, key: String },
+}
+
+impl StringTable {
+    /// Parses the content of a string table.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut entries = HashMap::new();
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(ParseError::MissingSeparator { line: line_number })?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(ParseError::EmptyKey { line: line_number });
+            }
+
+            if entries
+                .insert(key.to_owned(), unescape(value.trim()))
+                .is_some()
+            {
+                return Err(ParseError::DuplicateKey {
+                    line: line_number,
+                    key: key.to_owned(),
+                });
+            }
+        }
+
+        Ok(StringTable { entries })
+    }
+
+    /// Returns the string of `key`, if the table has it.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// Returns the keys of the table, with the plural suffixes removed.
+    pub fn base_keys(&self) -> BTreeSet<&str> {
+        self.entries.keys().map(|key| base_key(key)).collect()
+    }
+
+    /// Returns the keys of `reference` (without plural suffixes) that this table lacks.
+    pub fn missing_keys<'a>(&self, reference: &'a StringTable) -> Vec<&'a str> {
+        let keys = self.base_keys();
+
+        reference
+            .base_keys()
+            .into_iter()
+            .filter(|key| !keys.contains(key))
+            .collect()
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingSeparator { line } => write!(f, "line {line}: expected `key = value`"),
+            Self::EmptyKey { line } => write!(f, "line {line}: empty key"),
+            Self::DuplicateKey { line, key } => write!(f, "line {line}: duplicate key `{key}`"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Removes the plural suffix of `key`, if any.
+pub fn base_key(key: &str) -> &str {
+    PLURAL_SUFFIXES
+        .iter()
+        .find_map(|suffix| key.strip_suffix(suffix))
+        .unwrap_or(key)
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                result.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                result.push('\\');
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
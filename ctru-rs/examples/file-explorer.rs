@@ -162,17 +162,16 @@ impl<'a> FileExplorer<'a> {
 
     fn get_input_and_run(&mut self, action: impl FnOnce(&mut Self, String)) {
         let mut keyboard = Swkbd::default();
-        let mut new_path_str = String::new();
 
-        match keyboard.get_utf8(&mut new_path_str) {
-            Ok(Button::Right) => {
+        match keyboard.launch().map(|output| (output.button, output.text)) {
+            Ok((Button::Right, new_path_str)) => {
                 // Clicked "OK"
                 action(self, new_path_str);
             }
-            Ok(Button::Left) => {
+            Ok((Button::Left, _)) => {
                 // Clicked "Cancel"
             }
-            Ok(Button::Middle) => {
+            Ok((Button::Middle, _)) => {
                 // This button wasn't shown
                 unreachable!()
             }
@@ -0,0 +1,57 @@
+use ctru::i18n::Localizer;
+use ctru::prelude::*;
+use ctru::services::cfgu::{Cfgu, Language};
+use ctru::tr;
+
+fn main() {
+    ctru::use_panic_handler();
+
+    let gfx = Gfx::init().expect("Couldn't obtain GFX controller");
+    let hid = Hid::init().expect("Couldn't obtain HID controller");
+    let apt = Apt::init().expect("Couldn't obtain APT controller");
+    let cfgu = Cfgu::init().expect("Couldn't obtain CFGU controller");
+    let _console = Console::init(gfx.top_screen.borrow_mut());
+
+    // The string tables are stored in the RomFS, in `examples/romfs/lang`
+    #[cfg(all(feature = "romfs", romfs_exists))]
+    let _romfs = ctru::romfs::RomFS::init().unwrap();
+
+    // Strings missing from the console's language are looked up in English
+    let strings = Localizer::load_for_system(&cfgu, "romfs:/lang", &[Language::English])
+        .expect("Couldn't load the string tables");
+
+    let language = cfgu.get_language().unwrap();
+    println!("{}\n", tr!(strings, "title"));
+    println!("{}", tr!(strings, "greeting", name = "Ferris"));
+    println!(
+        "{}",
+        tr!(strings, "language", language = language.bcp47_tag())
+    );
+    println!("\x1b[29;0H{}", tr!(strings, "quit"));
+
+    let mut presses = 0;
+
+    // Main loop
+    while apt.main_loop() {
+        //Scan all the inputs. This should be done once for each frame
+        hid.scan_input();
+
+        if hid.keys_down().contains(KeyPad::KEY_A) {
+            presses += 1;
+            println!(
+                "\x1b[6;0H\x1b[K{}",
+                tr!(strings, "presses", count = presses)
+            );
+        }
+
+        if hid.keys_down().contains(KeyPad::KEY_START) {
+            break;
+        }
+        // Flush and swap framebuffers
+        gfx.flush_buffers();
+        gfx.swap_buffers();
+
+        //Wait for VBlank
+        gfx.wait_for_vblank();
+    }
+}
@@ -1,4 +1,4 @@
-use ctru::applets::mii_selector::MiiSelector;
+use ctru::applets::mii_selector::{MiiSelector, SelectedMii};
 use ctru::prelude::*;
 
 fn main() {
@@ -10,20 +10,30 @@ fn main() {
     let _console = Console::init(gfx.top_screen.borrow_mut());
 
     let mut mii_selector = MiiSelector::init();
-    mii_selector.set_initial_index(3);
-    mii_selector.blacklist_user_mii(0.into());
-    mii_selector.set_title("Great Mii Selector!");
-
-    let result = mii_selector.launch().unwrap();
-
-    println!("Is Mii selected?: {:?}", result.is_mii_selected);
-    println!("Mii type: {:?}", result.mii_type);
-    println!("Name: {:?}", result.mii_data.name);
-    println!("Author: {:?}", result.mii_data.author_name);
-    println!(
-        "Does the Mii have moles?: {:?}",
-        result.mii_data.mole_details.is_enabled
-    );
+    mii_selector
+        .set_initial_index(3)
+        .blacklist_user_mii(0.into())
+        .set_title("Great Mii Selector!");
+
+    match mii_selector.launch(&apt, &gfx).unwrap() {
+        Some(selected) => {
+            match &selected {
+                SelectedMii::User { .. } => println!("Mii type: User"),
+                SelectedMii::Guest { index, name, .. } => {
+                    println!("Mii type: Guest #{index} ({name})")
+                }
+            }
+
+            let mii = selected.mii();
+            println!("Name: {:?}", mii.name);
+            println!("Author: {:?}", mii.author_name);
+            println!(
+                "Does the Mii have moles?: {:?}",
+                mii.mole_details.is_enabled
+            );
+        }
+        None => println!("No Mii selected"),
+    }
 
     // Main loop
     while apt.main_loop() {
@@ -23,16 +23,16 @@ fn main() {
             // to accept it. You can also use `Swkbd::init()` to launch the keyboard in different
             // configurations.
             let mut keyboard = Swkbd::default();
-
-            // String used to store text received from the keyboard
-            let mut text = String::new();
+            keyboard.set_initial_text("Hello!");
 
             // Raise the software keyboard. You can perform different actions depending on which
             // software button the user pressed
-            match keyboard.get_utf8(&mut text) {
-                Ok(Button::Right) => println!("You entered: {text}"),
-                Ok(Button::Left) => println!("Cancelled"),
-                Ok(Button::Middle) => println!("How did you even press this?"),
+            match keyboard.launch() {
+                Ok(output) => match output.button {
+                    Button::Right => println!("You entered: {}", output.text),
+                    Button::Left => println!("Cancelled"),
+                    Button::Middle => println!("How did you even press this?"),
+                },
                 Err(_) => println!("Oh noes, an error happened!"),
             }
         }
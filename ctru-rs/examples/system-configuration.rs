@@ -13,6 +13,8 @@ fn main() {
     println!("\x1b[0;0HRegion: {:?}", cfgu.get_region().unwrap());
     println!("\x1b[10;0HLanguage: {:?}", cfgu.get_language().unwrap());
     println!("\x1b[20;0HModel: {:?}", cfgu.get_model().unwrap());
+    println!("\x1b[22;0HUser: {}", cfgu.get_user_name().unwrap());
+    println!("\x1b[24;0HBirthday: {:?}", cfgu.get_birthday().unwrap());
 
     // Main loop
     while apt.main_loop() {
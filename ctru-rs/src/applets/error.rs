@@ -0,0 +1,164 @@
+//! Error applet
+//!
+//! This module contains the methods to show an error with the system's error UI.
+
+use crate::applets::LibraryApplet;
+use crate::error::ResultCode;
+use crate::gfx::Gfx;
+use crate::services::apt::{AppId, Apt};
+use crate::services::cfgu::Language;
+use std::{mem, ptr, slice};
+
+/// The kind of message shown by the error applet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Kind {
+    /// Shows an error code, along with the system's description of it.
+    Code = ctru_sys::ERROR_CODE,
+    /// Shows a custom text.
+    Text = ctru_sys::ERROR_TEXT,
+    Eula = ctru_sys::ERROR_EULA,
+    EulaFirstBoot = ctru_sys::ERROR_TYPE_EULA_FIRST_BOOT,
+    EulaDrawOnly = ctru_sys::ERROR_TYPE_EULA_DRAW_ONLY,
+    Agree = ctru_sys::ERROR_TYPE_AGREE,
+}
+
+/// How the user closed the error applet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ErrorReturn {
+    Unknown = ctru_sys::ERROR_UNKNOWN,
+    None = ctru_sys::ERROR_NONE,
+    /// The user dismissed the error.
+    Success = ctru_sys::ERROR_SUCCESS,
+    NotSupported = ctru_sys::ERROR_NOT_SUPPORTED,
+    /// The user pressed the HOME button.
+    HomeButton = ctru_sys::ERROR_HOME_BUTTON,
+    /// The user asked for a software reset.
+    SoftwareReset = ctru_sys::ERROR_SOFTWARE_RESET,
+    /// The user pressed the POWER button.
+    PowerButton = ctru_sys::ERROR_POWER_BUTTON,
+}
+
+/// An instance of the error applet
+///
+/// # Example
+/// ```no_run
+/// use ctru::applets::error::{ErrorApplet, Kind};
+/// use ctru::prelude::*;
+///
+/// let gfx = Gfx::init().unwrap();
+/// let apt = Apt::init().unwrap();
+///
+/// let mut error = ErrorApplet::new(Kind::Text);
+/// error.set_text("The save file is corrupted.");
+/// error.launch(&apt, &gfx);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ErrorApplet {
+    state: Box<ctru_sys::errorConf>,
+}
+
+impl ErrorApplet {
+    /// Initializes an error applet of the given kind.
+    pub fn new(kind: Kind) -> Self {
+        let mut state = Box::<ctru_sys::errorConf>::default();
+        unsafe { ctru_sys::errorInit(state.as_mut(), kind as u32, ctru_sys::CFG_LANGUAGE_EN) };
+        Self { state }
+    }
+
+    /// Initializes an error applet describing the given error.
+    ///
+    /// Result codes are shown with [`Kind::Code`], other errors with their description as a [`Kind::Text`].
+    pub fn from_error(error: &crate::Error) -> Self {
+        match error {
+            crate::Error::Os(code) => {
+                let mut applet = Self::new(Kind::Code);
+                applet.set_code(ResultCode(*code));
+                applet
+            }
+            other => {
+                let mut applet = Self::new(Kind::Text);
+                applet.set_text(&other.to_string());
+                applet
+            }
+        }
+    }
+
+    /// Sets the error code to show.
+    pub fn set_code(&mut self, code: ResultCode) {
+        self.state.errorCode = code.0;
+    }
+
+    /// Sets the text to show.
+    ///
+    /// Texts longer than what the applet can show are truncated.
+    pub fn set_text(&mut self, text: &str) {
+        // Leave room for the NUL terminator.
+        let max_len = self.state.Text.len() - 1;
+
+        self.state.Text.fill(0);
+        for (dst, src) in self.state.Text[..max_len]
+            .iter_mut()
+            .zip(text.encode_utf16())
+        {
+            *dst = src;
+        }
+    }
+
+    /// Shows the applet in the given language instead of the system one.
+    pub fn set_language(&mut self, language: Language) {
+        self.state.useLanguage = u8::from(language).into();
+        self.state.type_ |= ctru_sys::ERROR_LANGUAGE_FLAG;
+    }
+
+    /// Enables or disables word wrapping of the text.
+    pub fn set_word_wrap(&mut self, enabled: bool) {
+        if enabled {
+            self.state.type_ |= ctru_sys::ERROR_WORD_WRAP_FLAG;
+        } else {
+            self.state.type_ &= !ctru_sys::ERROR_WORD_WRAP_FLAG;
+        }
+    }
+
+    /// Launches the error applet and blocks until the user closes it.
+    pub fn launch(&mut self, apt: &Apt, gfx: &Gfx) -> ErrorReturn {
+        let parameter = unsafe {
+            slice::from_raw_parts(
+                self.state.as_ref() as *const ctru_sys::errorConf as *const u8,
+                mem::size_of::<ctru_sys::errorConf>(),
+            )
+        };
+        let mut output = [0; mem::size_of::<ctru_sys::errorConf>()];
+
+        LibraryApplet::new(AppId::Error).launch(apt, gfx, parameter, &mut output, None);
+
+        *self.state = unsafe { ptr::read_unaligned(output.as_ptr() as *const _) };
+        self.state.returnCode.into()
+    }
+
+    /// Launches the error applet without checking that the needed services are running.
+    ///
+    /// # Safety
+    ///
+    /// APT and the graphics must be initialized.
+    #[cfg(not(test))] // Only used by the panic handler.
+    pub(crate) unsafe fn launch_unchecked(&mut self) -> ErrorReturn {
+        ctru_sys::errorDisp(self.state.as_mut());
+        self.state.returnCode.into()
+    }
+}
+
+impl From<ctru_sys::errorReturnCode> for ErrorReturn {
+    fn from(value: ctru_sys::errorReturnCode) -> Self {
+        match value {
+            ctru_sys::ERROR_NONE => ErrorReturn::None,
+            ctru_sys::ERROR_SUCCESS => ErrorReturn::Success,
+            ctru_sys::ERROR_NOT_SUPPORTED => ErrorReturn::NotSupported,
+            ctru_sys::ERROR_HOME_BUTTON => ErrorReturn::HomeButton,
+            ctru_sys::ERROR_SOFTWARE_RESET => ErrorReturn::SoftwareReset,
+            ctru_sys::ERROR_POWER_BUTTON => ErrorReturn::PowerButton,
+            _ => ErrorReturn::Unknown,
+        }
+    }
+}
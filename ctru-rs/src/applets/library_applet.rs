@@ -0,0 +1,79 @@
+//! Library applet launcher
+//!
+//! Library applets are system applets, such as the software keyboard or the Mii Selector, which run
+//! on top of the application while it's suspended. This module contains the common launch path used
+//! to wrap them.
+
+use crate::gfx::Gfx;
+use crate::services::apt::{AppId, Apt, ParameterHandle};
+
+/// A library applet, identified by its [`AppId`].
+///
+/// # Example
+/// ```no_run
+/// use ctru::applets::LibraryApplet;
+/// use ctru::services::apt::{AppId, Apt};
+/// use ctru::gfx::Gfx;
+///
+/// let gfx = Gfx::init().unwrap();
+/// let apt = Apt::init().unwrap();
+///
+/// let parameter = [0u8; 0x100];
+/// let mut output = [0u8; 0x100];
+/// LibraryApplet::new(AppId::Web).launch(&apt, &gfx, &parameter, &mut output, None);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LibraryApplet {
+    id: AppId,
+}
+
+impl LibraryApplet {
+    /// Creates a launcher for the applet identified by `id`.
+    pub fn new(id: AppId) -> Self {
+        Self { id }
+    }
+
+    /// Returns the ID of the applet.
+    pub fn id(&self) -> AppId {
+        self.id
+    }
+
+    /// Launches the applet and blocks until it exits.
+    ///
+    /// `parameter` is sent to the applet on launch, along with `handle`, and the data returned by the
+    /// applet is written to `output`. Applets use the same buffer for both, so `parameter` is padded with
+    /// zeros if `output` is longer, and the part of `output` the applet doesn't write to is zeroed.
+    ///
+    /// While the applet runs, the screens show a capture of the last frame the application
+    /// rendered, and the application gets the screens back once the applet exits. The application
+    /// receives the [`AptEvent::Suspend`](crate::services::apt::AptEvent::Suspend) and
+    /// [`AptEvent::Restore`](crate::services::apt::AptEvent::Restore) events around the launch.
+    ///
+    /// If the user closes the application from the applet (e.g. through the HOME menu), this returns early
+    /// and [`Apt::main_loop`] returns `false`.
+    pub fn launch(
+        &self,
+        _apt: &Apt,
+        gfx: &Gfx,
+        parameter: &[u8],
+        output: &mut [u8],
+        handle: Option<&ParameterHandle>,
+    ) {
+        let mut buffer = vec![0; parameter.len().max(output.len())];
+        buffer[..parameter.len()].copy_from_slice(parameter);
+
+        // libctru captures the framebuffers to show them under the applet: make sure they hold the latest frame.
+        gfx.flush_buffers();
+
+        unsafe {
+            ctru_sys::aptLaunchLibraryApplet(
+                self.id.into(),
+                buffer.as_mut_ptr() as *mut _,
+                buffer.len(),
+                handle.map_or(0, ParameterHandle::as_raw),
+            );
+        }
+
+        output.copy_from_slice(&buffer[..output.len()]);
+    }
+}
@@ -2,9 +2,15 @@
 //!
 //! This module contains the methods to launch the Mii Selector.
 
-use crate::mii::MiiData;
+use crate::applets::LibraryApplet;
+use crate::gfx::Gfx;
+use crate::mii::database::MiiDatabase;
+use crate::mii::{MiiData, MiiDataError};
+use crate::services::apt::{AppId, Apt};
 use bitflags::bitflags;
 use std::ffi::CString;
+use std::ops::{Bound, Range, RangeBounds};
+use std::{mem, slice};
 
 /// Index of a Mii used to configure some parameters of the Mii Selector
 /// Can be either a single index, or _all_ Miis
@@ -14,11 +20,33 @@ pub enum MiiConfigIndex {
     All,
 }
 
-/// The type of a Mii with their respective data
+/// The Mii picked by the user in the Mii Selector
 #[derive(Debug, Clone)]
-pub enum MiiType {
-    Guest { index: u32, name: String },
-    User,
+pub enum SelectedMii {
+    /// A Mii made by the user, stored on the console
+    User { mii: MiiData },
+    /// One of the guest Miis built into the console, with their localized name
+    Guest {
+        index: u32,
+        name: String,
+        mii: MiiData,
+    },
+}
+
+impl SelectedMii {
+    /// Returns the data of the selected Mii.
+    pub fn mii(&self) -> &MiiData {
+        match self {
+            SelectedMii::User { mii } | SelectedMii::Guest { mii, .. } => mii,
+        }
+    }
+
+    /// Returns the data of the selected Mii.
+    pub fn into_mii(self) -> MiiData {
+        match self {
+            SelectedMii::User { mii } | SelectedMii::Guest { mii, .. } => mii,
+        }
+    }
 }
 
 bitflags! {
@@ -37,35 +65,47 @@ bitflags! {
 
 /// An instance of the Mii Selector
 ///
+/// The configuration methods return `&mut Self`, so they can be chained.
+///
 /// # Example
 /// ```
-/// use ctru::applets::mii_selector::MiiSelector;
+/// use ctru::applets::mii_selector::{MiiSelector, Options};
+/// use ctru::prelude::*;
+///
+/// let gfx = Gfx::init().unwrap();
+/// let apt = Apt::init().unwrap();
 ///
 /// let mut mii_selector = MiiSelector::init();
-/// mii_selector.set_title("Example Mii selector");
+/// mii_selector
+///     .set_title("Example Mii selector")
+///     .set_options(Options::MII_SELECTOR_CANCEL)
+///     .blacklist_user_miis(10..);
 ///
-/// let result = mii_selector.launch().unwrap();
+/// match mii_selector.launch(&apt, &gfx).unwrap() {
+///     Some(selected) => println!("Hello, {}!", selected.mii().name),
+///     None => println!("No Mii selected"),
+/// }
 /// ```
 #[derive(Clone, Debug)]
 pub struct MiiSelector {
     config: Box<ctru_sys::MiiSelectorConf>,
 }
 
-/// Return value from a MiiSelector's launch
-#[non_exhaustive]
-#[derive(Clone, Debug)]
-pub struct MiiSelectorReturn {
-    pub mii_data: MiiData,
-    pub is_mii_selected: bool,
-    pub mii_type: MiiType,
-}
-
 /// Error type for the Mii selector
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum MiiLaunchError {
+    /// The checksum of the returned Mii doesn't match its data.
     InvalidChecksum,
+    /// The name of the selected guest Mii isn't valid UTF-16.
+    InvalidGuestName,
+    /// The returned Mii couldn't be decoded.
+    InvalidMiiData(MiiDataError),
 }
 
+/// Error returned by [`MiiSelector::set_initial_mii`] when the Mii isn't in the database
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MiiNotInDatabase;
+
 impl MiiSelector {
     /// Initializes a Mii Selector
     pub fn init() -> Self {
@@ -79,104 +119,190 @@ impl MiiSelector {
     /// Set the title of the Mii Selector.
     ///
     /// This function would panic if the given ``&str`` contains NUL bytes.
-    pub fn set_title(&mut self, text: &str) {
+    pub fn set_title(&mut self, text: &str) -> &mut Self {
         // This can only fail if the text contains NUL bytes in the string... which seems
         // unlikely and is documented
         let c_text = CString::new(text).expect("Failed to convert the title text into a CString");
         unsafe {
             ctru_sys::miiSelectorSetTitle(self.config.as_mut(), c_text.as_ptr());
         }
+        self
     }
 
     /// Set the options of the Mii Selector
-    pub fn set_options(&mut self, options: Options) {
+    pub fn set_options(&mut self, options: Options) -> &mut Self {
         unsafe { ctru_sys::miiSelectorSetOptions(self.config.as_mut(), options.bits) }
+        self
     }
 
     /// Whitelist a guest Mii
-    pub fn whitelist_guest_mii(&mut self, mii_index: MiiConfigIndex) {
+    pub fn whitelist_guest_mii(&mut self, mii_index: MiiConfigIndex) -> &mut Self {
         let index = match mii_index {
             MiiConfigIndex::Index(i) => i,
             MiiConfigIndex::All => ctru_sys::MIISELECTOR_GUESTMII_SLOTS,
         };
 
         unsafe { ctru_sys::miiSelectorWhitelistGuestMii(self.config.as_mut(), index) }
+        self
     }
 
     /// Blacklist a guest Mii
-    pub fn blacklist_guest_mii(&mut self, mii_index: MiiConfigIndex) {
+    pub fn blacklist_guest_mii(&mut self, mii_index: MiiConfigIndex) -> &mut Self {
         let index = match mii_index {
             MiiConfigIndex::Index(i) => i,
             MiiConfigIndex::All => ctru_sys::MIISELECTOR_GUESTMII_SLOTS,
         };
 
         unsafe { ctru_sys::miiSelectorBlacklistGuestMii(self.config.as_mut(), index) }
+        self
     }
 
     /// Whitelist a user Mii
-    pub fn whitelist_user_mii(&mut self, mii_index: MiiConfigIndex) {
+    pub fn whitelist_user_mii(&mut self, mii_index: MiiConfigIndex) -> &mut Self {
         let index = match mii_index {
             MiiConfigIndex::Index(i) => i,
             MiiConfigIndex::All => ctru_sys::MIISELECTOR_USERMII_SLOTS,
         };
 
         unsafe { ctru_sys::miiSelectorWhitelistUserMii(self.config.as_mut(), index) }
+        self
     }
 
     /// Blacklist a user Mii
-    pub fn blacklist_user_mii(&mut self, mii_index: MiiConfigIndex) {
+    pub fn blacklist_user_mii(&mut self, mii_index: MiiConfigIndex) -> &mut Self {
         let index = match mii_index {
             MiiConfigIndex::Index(i) => i,
             MiiConfigIndex::All => ctru_sys::MIISELECTOR_USERMII_SLOTS,
         };
 
         unsafe { ctru_sys::miiSelectorBlacklistUserMii(self.config.as_mut(), index) }
+        self
+    }
+
+    /// Whitelist the user Miis whose index is in `range`
+    ///
+    /// Indices past the last Mii slot are ignored.
+    pub fn whitelist_user_miis<R: RangeBounds<u32>>(&mut self, range: R) -> &mut Self {
+        self.set_user_miis_enabled(range, true);
+        self
+    }
+
+    /// Blacklist the user Miis whose index is in `range`
+    ///
+    /// Indices past the last Mii slot are ignored.
+    pub fn blacklist_user_miis<R: RangeBounds<u32>>(&mut self, range: R) -> &mut Self {
+        self.set_user_miis_enabled(range, false);
+        self
     }
 
     /// Set where the cursor will be.
     /// If there's no Mii at that index, the cursor will start at the Mii with the index 0
-    pub fn set_initial_index(&mut self, index: u32) {
+    pub fn set_initial_index(&mut self, index: u32) -> &mut Self {
         // This function is static inline in libctru
         // https://github.com/devkitPro/libctru/blob/af5321c78ee5c72a55b526fd2ed0d95ca1c05af9/libctru/include/3ds/applets/miiselector.h#L155
-        self.config.initial_index = index
+        self.config.initial_index = index;
+        self
+    }
+
+    /// Set the cursor on the user Mii `mii`, looking for its index in `database`.
+    ///
+    /// Returns an error, leaving the cursor untouched, if the Mii isn't in the database.
+    pub fn set_initial_mii(
+        &mut self,
+        mii: &MiiData,
+        database: &MiiDatabase,
+    ) -> Result<&mut Self, MiiNotInDatabase> {
+        match database.iter().find(|(_, other)| other.is_same_mii(mii)) {
+            Some((index, _)) => Ok(self.set_initial_index(index as u32)),
+            None => Err(MiiNotInDatabase),
+        }
     }
 
     /// Launch the Mii Selector.
-    /// Returns an error when the checksum of the Mii is invalid.
-    pub fn launch(&mut self) -> Result<MiiSelectorReturn, MiiLaunchError> {
-        let mut return_val = Box::<ctru_sys::MiiSelectorReturn>::default();
-        unsafe { ctru_sys::miiSelectorLaunch(self.config.as_mut(), return_val.as_mut()) }
-
-        if unsafe { ctru_sys::miiSelectorChecksumIsValid(return_val.as_mut()) } {
-            Ok((*return_val).into())
-        } else {
-            Err(MiiLaunchError::InvalidChecksum)
+    ///
+    /// Returns `None` if the user cancelled the selection, and an error when the returned data
+    /// isn't valid.
+    pub fn launch(&mut self, apt: &Apt, gfx: &Gfx) -> Result<Option<SelectedMii>, MiiLaunchError> {
+        // Same as `miiSelectorLaunch`, which is a thin wrapper around `aptLaunchLibraryApplet`.
+        let mut config = *self.config;
+        config.magic = ctru_sys::MIISELECTOR_MAGIC;
+
+        let parameter = unsafe {
+            slice::from_raw_parts(
+                &config as *const ctru_sys::MiiSelectorConf as *const u8,
+                mem::size_of::<ctru_sys::MiiSelectorConf>(),
+            )
+        };
+        let mut output = [0; mem::size_of::<ctru_sys::MiiSelectorReturn>()];
+
+        LibraryApplet::new(AppId::AppletEd).launch(apt, gfx, parameter, &mut output, None);
+
+        parse_return(&output)
+    }
+
+    fn set_user_miis_enabled<R: RangeBounds<u32>>(&mut self, range: R, enabled: bool) {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start as usize,
+            Bound::Excluded(&start) => (start as usize).saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => (end as usize).saturating_add(1),
+            Bound::Excluded(&end) => end as usize,
+            Bound::Unbounded => usize::MAX,
+        };
+
+        let whitelist = &mut self.config.mii_whitelist;
+        let end = end.min(whitelist.len());
+        if start < end {
+            whitelist[start..end].fill(enabled.into());
         }
     }
 }
 
-impl From<ctru_sys::MiiSelectorReturn> for MiiSelectorReturn {
-    fn from(ret: ctru_sys::MiiSelectorReturn) -> Self {
-        let raw_mii_data = ret.mii;
-        let mut guest_mii_name = ret.guest_mii_name;
-
-        MiiSelectorReturn {
-            mii_data: raw_mii_data.into(),
-            is_mii_selected: ret.no_mii_selected == 0,
-            mii_type: if ret.guest_mii_index != 0xFFFFFFFF {
-                MiiType::Guest {
-                    index: ret.guest_mii_index,
-                    name: {
-                        let utf16_be = &mut guest_mii_name;
-                        utf16_be.reverse();
-                        String::from_utf16(utf16_be.as_slice()).unwrap()
-                    },
-                }
-            } else {
-                MiiType::User
-            },
-        }
+// Layout of `ctru_sys::MiiSelectorReturn`
+const GUEST_SELECTED_OFFSET: usize = 0x4;
+const GUEST_INDEX_OFFSET: usize = 0x8;
+/// The Mii data, its padding and its checksum, laid out like the data given by `MiiData::to_bytes_with_checksum`
+const MII_RANGE: Range<usize> = 0xC..0x6C;
+const GUEST_NAME_RANGE: Range<usize> = 0x6C..0x84;
+
+/// Decodes the data written by the applet, without going through `ctru_sys::MiiSelectorReturn`
+/// so that no field is trusted before being checked.
+fn parse_return(output: &[u8]) -> Result<Option<SelectedMii>, MiiLaunchError> {
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes([
+            output[offset],
+            output[offset + 1],
+            output[offset + 2],
+            output[offset + 3],
+        ])
+    };
+
+    if read_u32(0) != 0 {
+        return Ok(None);
     }
+
+    let mii = MiiData::from_bytes(&output[MII_RANGE]).map_err(|e| match e {
+        MiiDataError::InvalidChecksum => MiiLaunchError::InvalidChecksum,
+        e => MiiLaunchError::InvalidMiiData(e),
+    })?;
+
+    if read_u32(GUEST_SELECTED_OFFSET) == 0 {
+        return Ok(Some(SelectedMii::User { mii }));
+    }
+
+    let name = output[GUEST_NAME_RANGE]
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .take_while(|&c| c != 0)
+        .collect::<Vec<u16>>();
+
+    Ok(Some(SelectedMii::Guest {
+        index: read_u32(GUEST_INDEX_OFFSET),
+        name: String::from_utf16(&name).map_err(|_| MiiLaunchError::InvalidGuestName)?,
+        mii,
+    }))
 }
 
 impl From<u32> for MiiConfigIndex {
@@ -1,2 +1,6 @@
+pub mod error;
+pub mod library_applet;
 pub mod mii_selector;
 pub mod swkbd;
+
+pub use self::library_applet::LibraryApplet;
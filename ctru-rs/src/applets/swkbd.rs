@@ -1,14 +1,83 @@
 use bitflags::bitflags;
 use ctru_sys::{
-    self, swkbdInit, swkbdInputText, swkbdSetButton, swkbdSetFeatures, swkbdSetHintText, SwkbdState,
+    self, swkbdInit, swkbdInputText, swkbdSetButton, swkbdSetDictionary, swkbdSetFeatures,
+    swkbdSetFilterCallback, swkbdSetHintText, swkbdSetInitialText, swkbdSetLearningData,
+    swkbdSetStatusData, SwkbdDictWord, SwkbdLearningData, SwkbdState, SwkbdStatusData,
 };
 use libc;
+use std::alloc::{self, Layout};
+use std::any::Any;
+use std::borrow::Cow;
+use std::ffi::CString;
 use std::iter::once;
-use std::str;
+use std::panic::{self, AssertUnwindSafe};
+use std::{fmt, mem, ptr, slice};
 
 /// An instance of the software keyboard.
 pub struct Swkbd {
     state: Box<SwkbdState>,
+    filter_callback: Option<Box<FilterCallback>>,
+    // The following are only referenced by pointer in `state`, so they must live as long as it.
+    initial_text: Option<CString>,
+    dictionary: Vec<SwkbdDictWord>,
+    status_data: Option<StatusData>,
+    learning_data: Option<LearningData>,
+}
+
+/// The input received from the software keyboard, see [`Swkbd::launch`].
+#[derive(Clone, Debug)]
+pub struct Output {
+    /// The text typed by the user.
+    pub text: String,
+    /// The button pressed to close the keyboard.
+    pub button: Button,
+    /// The state of the keyboard when it closed, if [`Swkbd::set_status_data`] asked for it.
+    pub status_data: Option<StatusData>,
+    /// The predictive input learning data, if [`Swkbd::set_learning_data`] asked for it.
+    pub learning_data: Option<LearningData>,
+}
+
+/// How the keyboard hides the typed characters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PasswordMode {
+    /// Characters are shown.
+    None = ctru_sys::SWKBD_PASSWORD_NONE,
+    /// Characters are hidden immediately.
+    Hide = ctru_sys::SWKBD_PASSWORD_HIDE,
+    /// Characters are hidden a second after being typed.
+    HideDelay = ctru_sys::SWKBD_PASSWORD_HIDE_DELAY,
+}
+
+/// A word of the predictive input dictionary, see [`Swkbd::set_dictionary`].
+#[derive(Copy, Clone, Debug)]
+pub struct DictWord(SwkbdDictWord);
+
+/// The state of the keyboard (such as the last page shown), to be restored the next time it's launched.
+#[derive(Clone, Debug, Default)]
+pub struct StatusData(Box<SwkbdStatusData>);
+
+/// The words learned by the predictive input system, to be restored the next time the keyboard is launched.
+pub struct LearningData(Box<SwkbdLearningData>);
+
+/// The value returned by a filter callback, see [`Swkbd::set_filter_callback`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallbackResult {
+    /// The input is accepted and the keyboard closes.
+    Ok,
+    /// The message is shown to the user, and the keyboard stays open for them to fix the input.
+    Continue(Cow<'static, str>),
+    /// The message is shown to the user, then the keyboard closes with [`Error::BannedInput`].
+    Close(Cow<'static, str>),
+}
+
+/// A filter callback with the data it needs across the FFI boundary.
+struct FilterCallback {
+    callback: Box<dyn FnMut(&str) -> CallbackResult>,
+    /// The last message returned by the callback, which libctru reads after the callback returns.
+    message: CString,
+    /// The payload of a panic caught in the callback, to be resumed once the keyboard is closed.
+    panic: Option<Box<dyn Any + Send>>,
 }
 
 /// The kind of keyboard to be initialized.
@@ -91,32 +160,61 @@ impl Swkbd {
         unsafe {
             let mut state = Box::<SwkbdState>::default();
             swkbdInit(state.as_mut(), keyboard_type as u32, num_buttons, -1);
-            Swkbd { state }
+            Swkbd {
+                state,
+                filter_callback: None,
+                initial_text: None,
+                dictionary: Vec::new(),
+                status_data: None,
+                learning_data: None,
+            }
         }
     }
 
-    /// Gets input from this keyboard and appends it to the provided string.
+    /// Launches the keyboard and returns what the user typed.
     ///
-    /// The text received from the keyboard will be truncated if it is greater than 2048 bytes
-    /// in length.
-    pub fn get_utf8(&mut self, buf: &mut String) -> Result<Button, Error> {
-        // Unfortunately the libctru API doesn't really provide a way to get the exact length
-        // of the string that it receieves from the software keyboard. Instead it expects you
-        // to pass in a buffer and hope that it's big enough to fit the entire string, so
-        // you have to set some upper limit on the potential size of the user's input.
-        const MAX_BYTES: usize = 2048;
-        let mut tmp = [0u8; MAX_BYTES];
-        let button = self.get_bytes(&mut tmp)?;
+    /// # Example
+    /// ```no_run
+    /// use ctru::applets::swkbd::{Button, Swkbd};
+    ///
+    /// let mut keyboard = Swkbd::default();
+    /// keyboard.set_initial_text("Player");
+    ///
+    /// if let Ok(output) = keyboard.launch() {
+    ///     if let Button::Right = output.button {
+    ///         println!("Hello, {}!", output.text);
+    ///     }
+    /// }
+    /// ```
+    pub fn launch(&mut self) -> Result<Output, Error> {
+        // Each UTF-16 code unit typed in the keyboard is at most 3 bytes of UTF-8.
+        let mut buf = vec![0; self.state.max_text_len as usize * 3 + 1];
+        let button = self.input_text(&mut buf)?;
+
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        let text = String::from_utf8_lossy(&buf[..len]).into_owned();
+
+        Ok(Output {
+            text,
+            button,
+            status_data: self
+                .status_data
+                .clone()
+                .filter(|_| self.state.save_state_flags & SAVE_STATUS_DATA != 0),
+            learning_data: self
+                .learning_data
+                .clone()
+                .filter(|_| self.state.save_state_flags & SAVE_LEARNING_DATA != 0),
+        })
+    }
 
-        // libctru does, however, seem to ensure that the buffer will always contain a properly
-        // terminated UTF-8 sequence even if the input has to be truncated, so these operations
-        // should be safe.
-        let len = unsafe { libc::strlen(tmp.as_ptr()) };
-        let utf8 = unsafe { str::from_utf8_unchecked(&tmp[..len]) };
+    /// Gets input from this keyboard and appends it to the provided string.
+    #[deprecated(note = "use `Swkbd::launch` instead")]
+    pub fn get_utf8(&mut self, buf: &mut String) -> Result<Button, Error> {
+        let output = self.launch()?;
 
-        // Copy the input into the user's `String`
-        *buf += utf8;
-        Ok(button)
+        *buf += &output.text;
+        Ok(output.button)
     }
 
     /// Fills the provided buffer with a UTF-8 encoded, NUL-terminated sequence of bytes from
@@ -124,9 +222,36 @@ impl Swkbd {
     ///
     /// If the buffer is too small to contain the entire sequence received from the keyboard,
     /// the output will be truncated but should still be well-formed UTF-8
+    #[deprecated(note = "use `Swkbd::launch` instead")]
     pub fn get_bytes(&mut self, buf: &mut [u8]) -> Result<Button, Error> {
+        let output = self.launch()?;
+
+        if let Some(max_len) = buf.len().checked_sub(1) {
+            let mut len = output.text.len().min(max_len);
+            while !output.text.is_char_boundary(len) {
+                len -= 1;
+            }
+            buf[..len].copy_from_slice(&output.text.as_bytes()[..len]);
+            buf[len] = 0;
+        }
+        Ok(output.button)
+    }
+
+    /// Shows the keyboard, and fills `buf` with the NUL-terminated UTF-8 text typed by the user.
+    fn input_text(&mut self, buf: &mut [u8]) -> Result<Button, Error> {
         unsafe {
-            match swkbdInputText(self.state.as_mut(), buf.as_mut_ptr(), buf.len()) {
+            let button = swkbdInputText(self.state.as_mut(), buf.as_mut_ptr(), buf.len());
+
+            // The callback panicked: now that the keyboard is closed, we can unwind safely.
+            if let Some(payload) = self
+                .filter_callback
+                .as_mut()
+                .and_then(|filter| filter.panic.take())
+            {
+                panic::resume_unwind(payload);
+            }
+
+            match button {
                 ctru_sys::SWKBD_BUTTON_NONE => Err(self.parse_swkbd_error()),
                 ctru_sys::SWKBD_BUTTON_LEFT => Ok(Button::Left),
                 ctru_sys::SWKBD_BUTTON_MIDDLE => Ok(Button::Middle),
@@ -147,6 +272,133 @@ impl Swkbd {
         self.state.filter_flags = filters.bits;
     }
 
+    /// Sets the text shown in the keyboard when it opens.
+    ///
+    /// The text is cut at the first NUL character, if any.
+    pub fn set_initial_text(&mut self, text: &str) {
+        let text = text.split('\0').next().unwrap_or_default();
+        let text = CString::new(text).unwrap_or_default();
+
+        unsafe { swkbdSetInitialText(self.state.as_mut(), text.as_ptr()) };
+        self.initial_text = Some(text);
+    }
+
+    /// Configures how the keyboard hides the typed characters.
+    pub fn set_password_mode(&mut self, mode: PasswordMode) {
+        // This function is static inline in libctru
+        self.state.password_mode = mode as i32;
+    }
+
+    /// Configures the extra keys on the left and right of the numpad, when using [`Kind::Numpad`].
+    ///
+    /// `None` hides the key. Characters outside of the Basic Multilingual Plane can't be used.
+    pub fn set_numpad_keys(&mut self, left: Option<char>, right: Option<char>) {
+        let to_code_unit = |key: Option<char>| {
+            key.and_then(|key| u16::try_from(u32::from(key)).ok())
+                .unwrap_or(0)
+        };
+
+        // This function is static inline in libctru
+        self.state.numpad_keys = [to_code_unit(left), to_code_unit(right)];
+    }
+
+    /// Sets the words of the predictive input dictionary.
+    ///
+    /// The dictionary is only used when [`Features::PREDICTIVE_INPUT`] is enabled.
+    pub fn set_dictionary(&mut self, words: &[DictWord]) {
+        self.dictionary = words.iter().map(|word| word.0).collect();
+
+        unsafe {
+            swkbdSetDictionary(
+                self.state.as_mut(),
+                self.dictionary.as_ptr(),
+                self.dictionary.len() as i32,
+            )
+        };
+    }
+
+    /// Configures the keyboard state.
+    ///
+    /// If `initial` is set, the keyboard is restored to that state when it opens. If `save` is `true`,
+    /// the state of the keyboard when it closes is returned in [`Output::status_data`].
+    pub fn set_status_data(&mut self, initial: Option<StatusData>, save: bool) {
+        let load = initial.is_some();
+        self.status_data = initial.or_else(|| save.then(StatusData::default));
+
+        let data = self
+            .status_data
+            .as_mut()
+            .map_or(ptr::null_mut(), |data| data.0.as_mut() as *mut _);
+        unsafe { swkbdSetStatusData(self.state.as_mut(), data, load, save) };
+    }
+
+    /// Configures the predictive input learning data.
+    ///
+    /// If `initial` is set, the words learned in previous sessions are restored when the keyboard opens.
+    /// If `save` is `true`, the updated learning data is returned in [`Output::learning_data`].
+    pub fn set_learning_data(&mut self, initial: Option<LearningData>, save: bool) {
+        let load = initial.is_some();
+        self.learning_data = initial.or_else(|| save.then(LearningData::default));
+
+        let data = self
+            .learning_data
+            .as_mut()
+            .map_or(ptr::null_mut(), |data| data.0.as_mut() as *mut _);
+        unsafe { swkbdSetLearningData(self.state.as_mut(), data, load, save) };
+    }
+
+    /// Sets a closure to validate the input before the keyboard closes.
+    ///
+    /// The closure receives the text typed by the user when they press a button which submits it.
+    /// The messages it returns are shown in the keyboard, truncated to 256 UTF-16 code units.
+    ///
+    /// The callback is only called when the [`Filters::CALLBACK`] flag is set, which libctru does
+    /// automatically when launching the keyboard.
+    ///
+    /// # Notes
+    ///
+    /// If the closure panics, the keyboard is closed and the panic resumes in [`Swkbd::launch`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use ctru::applets::swkbd::{CallbackResult, Swkbd};
+    ///
+    /// let mut keyboard = Swkbd::default();
+    /// keyboard.set_filter_callback(|text| {
+    ///     if text.chars().count() < 3 {
+    ///         CallbackResult::Continue("The name is too short.".into())
+    ///     } else {
+    ///         CallbackResult::Ok
+    ///     }
+    /// });
+    /// ```
+    pub fn set_filter_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(&str) -> CallbackResult + 'static,
+    {
+        let mut filter = Box::new(FilterCallback {
+            callback: Box::new(callback),
+            message: CString::default(),
+            panic: None,
+        });
+
+        unsafe {
+            swkbdSetFilterCallback(
+                self.state.as_mut(),
+                Some(filter_callback_trampoline),
+                filter.as_mut() as *mut FilterCallback as *mut _,
+            );
+        }
+
+        self.filter_callback = Some(filter);
+    }
+
+    /// Removes the closure set with [`Swkbd::set_filter_callback`].
+    pub fn clear_filter_callback(&mut self) {
+        unsafe { swkbdSetFilterCallback(self.state.as_mut(), None, std::ptr::null_mut()) };
+        self.filter_callback = None;
+    }
+
     /// Configures the maximum number of digits that can be entered in the keyboard when the
     /// `Filters::DIGITS` flag is enabled
     pub fn set_max_digits(&mut self, digits: u16) {
@@ -185,7 +437,7 @@ impl Swkbd {
     ///
     /// Note that keyboard input is converted from UTF-16 to UTF-8 before being handed to Rust,
     /// so this code point limit does not necessarily equal the max number of UTF-8 code points
-    /// receivable by the `get_utf8` and `get_bytes` functions.
+    /// in the text returned by [`Swkbd::launch`].
     pub fn set_max_text_len(&mut self, len: u16) {
         self.state.max_text_len = len;
     }
@@ -205,6 +457,148 @@ impl Swkbd {
     }
 }
 
+impl DictWord {
+    /// Creates a dictionary word, suggested as `word` when the user types `reading`.
+    ///
+    /// Both are truncated to 40 UTF-16 code units.
+    pub fn new(reading: &str, word: &str) -> Self {
+        // Same as `swkbdSetDictWord`, without the detour through C strings.
+        let mut dict_word = SwkbdDictWord {
+            all_languages: true,
+            ..Default::default()
+        };
+        copy_utf16(&mut dict_word.reading, reading);
+        copy_utf16(&mut dict_word.word, word);
+
+        DictWord(dict_word)
+    }
+}
+
+impl StatusData {
+    /// Size of the status data, in bytes.
+    pub const SIZE: usize = mem::size_of::<SwkbdStatusData>();
+
+    /// Returns the raw status data, e.g. to write it to a file.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.0.as_ref() as *const _ as *const u8, Self::SIZE) }
+    }
+
+    /// Reads status data saved with [`StatusData::as_bytes`].
+    ///
+    /// Returns `None` if `bytes` isn't [`StatusData::SIZE`] bytes long.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut data = Self::default();
+        copy_raw(data.0.as_mut(), bytes)?;
+        Some(data)
+    }
+}
+
+impl LearningData {
+    /// Size of the learning data, in bytes.
+    pub const SIZE: usize = mem::size_of::<SwkbdLearningData>();
+
+    /// Returns the raw learning data, e.g. to write it to a file.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.0.as_ref() as *const _ as *const u8, Self::SIZE) }
+    }
+
+    /// Reads learning data saved with [`LearningData::as_bytes`].
+    ///
+    /// Returns `None` if `bytes` isn't [`LearningData::SIZE`] bytes long.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let mut data = Self::default();
+        copy_raw(data.0.as_mut(), bytes)?;
+        Some(data)
+    }
+}
+
+impl Default for LearningData {
+    fn default() -> Self {
+        // The learning data is too big for the stack, so it can't go through `Box::default`.
+        let layout = Layout::new::<SwkbdLearningData>();
+        unsafe {
+            let data = alloc::alloc_zeroed(layout) as *mut SwkbdLearningData;
+            if data.is_null() {
+                alloc::handle_alloc_error(layout);
+            }
+            LearningData(Box::from_raw(data))
+        }
+    }
+}
+
+impl Clone for LearningData {
+    fn clone(&self) -> Self {
+        let mut data = Self::default();
+        unsafe { ptr::copy_nonoverlapping(self.0.as_ref(), data.0.as_mut(), 1) };
+        data
+    }
+}
+
+impl fmt::Debug for LearningData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LearningData").finish_non_exhaustive()
+    }
+}
+
+/// Bit of `save_state_flags` set when the status data is saved.
+const SAVE_STATUS_DATA: u32 = 1 << 0;
+/// Bit of `save_state_flags` set when the learning data is saved.
+const SAVE_LEARNING_DATA: u32 = 1 << 1;
+
+/// Copies `text` into a NUL-terminated UTF-16 buffer, truncating it if needed.
+fn copy_utf16(dst: &mut [u16], text: &str) {
+    let max_len = dst.len() - 1;
+
+    dst.fill(0);
+    for (dst, src) in dst[..max_len].iter_mut().zip(text.encode_utf16()) {
+        *dst = src;
+    }
+}
+
+/// Copies `bytes` over the plain data `dst`, if the sizes match.
+fn copy_raw<T: Copy>(dst: &mut T, bytes: &[u8]) -> Option<()> {
+    if bytes.len() != mem::size_of::<T>() {
+        return None;
+    }
+
+    unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), dst as *mut T as *mut u8, bytes.len()) };
+    Some(())
+}
+
+unsafe extern "C" fn filter_callback_trampoline(
+    user: *mut libc::c_void,
+    pp_message: *mut *const libc::c_char,
+    text: *const libc::c_char,
+    text_len: usize,
+) -> ctru_sys::SwkbdCallbackResult {
+    // Safety: `user` points to the `FilterCallback` owned by the `Swkbd` which is being launched.
+    let filter = &mut *(user as *mut FilterCallback);
+
+    // libctru converts the input to UTF-8 before calling us, but it's better not to trust a truncated buffer.
+    let text = String::from_utf8_lossy(slice::from_raw_parts(text as *const u8, text_len));
+
+    let result = match panic::catch_unwind(AssertUnwindSafe(|| (filter.callback)(&text))) {
+        Ok(result) => result,
+        Err(payload) => {
+            filter.panic = Some(payload);
+            return ctru_sys::SWKBD_CALLBACK_CLOSE;
+        }
+    };
+
+    let (code, message) = match result {
+        CallbackResult::Ok => return ctru_sys::SWKBD_CALLBACK_OK,
+        CallbackResult::Continue(message) => (ctru_sys::SWKBD_CALLBACK_CONTINUE, message),
+        CallbackResult::Close(message) => (ctru_sys::SWKBD_CALLBACK_CLOSE, message),
+    };
+
+    // The message can't contain NUL bytes, so cut it at the first one.
+    let message = message.split('\0').next().unwrap_or_default();
+    filter.message = CString::new(message).unwrap_or_default();
+    *pp_message = filter.message.as_ptr();
+
+    code
+}
+
 impl Default for Swkbd {
     fn default() -> Self {
         Swkbd::init(Kind::Normal, 2)
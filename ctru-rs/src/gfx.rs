@@ -154,6 +154,12 @@ impl Gfx {
         })
     }
 
+    /// Returns `true` if the graphics are currently initialized.
+    #[cfg(not(test))] // Only used by the panic handler.
+    pub(crate) fn is_active() -> bool {
+        GFX_ACTIVE.try_lock().map_or(false, |count| *count > 0)
+    }
+
     /// Creates a new [Gfx] instance with default init values
     /// It's the same as calling:
     /// `Gfx::with_formats(FramebufferFormat::Bgr8, FramebufferFormat::Bgr8, false)`
@@ -0,0 +1,385 @@
+//! Localization
+//!
+//! This module loads the strings of an application from one [string table](table) per language,
+//! usually stored in the RomFS, and picks the ones matching the language of the console.
+//!
+//! The tables are named after the [BCP 47 tag](Language::bcp47_tag) of their language, e.g.
+//! `romfs:/lang/en.lang` or `romfs:/lang/zh-Hant.lang`. When a string is missing from the table
+//! of the console's language, the tables of the fallback languages are searched in order.
+//!
+//! The `ctru-i18n-check` crate, which this module uses to parse the tables, can also check them from the
+//! build script of the application, failing the build when a table is invalid or lacks some strings.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ctru::i18n::Localizer;
+//! use ctru::services::cfgu::{Cfgu, Language};
+//! use ctru::tr;
+//!
+//! let cfgu = Cfgu::init().unwrap();
+//! let _romfs = ctru::romfs::RomFS::init().unwrap();
+//!
+//! let strings = Localizer::load_for_system(&cfgu, "romfs:/lang", &[Language::English]).unwrap();
+//!
+//! println!("{}", tr!(strings, "greeting", name = "Ferris"));
+//! println!("{}", tr!(strings, "apples", count = 3));
+//! ```
+
+use crate::services::cfgu::{Cfgu, Language};
+use std::fmt::Display;
+use std::io;
+use std::path::Path;
+
+pub use ctru_i18n_check::{table, TABLE_EXTENSION};
+
+pub use self::table::{ParseError, StringTable};
+
+/// Plural category of a number, following the CLDR rules of each language
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+/// The strings of an application, in the console's language and its fallbacks
+#[derive(Clone, Debug, Default)]
+pub struct Localizer {
+    tables: Vec<(Language, StringTable)>,
+}
+
+impl PluralCategory {
+    /// Returns the plural category of `count` in `language`.
+    ///
+    /// Unknown languages use `Other` for every number.
+    pub fn of(language: Language, count: u64) -> Self {
+        match language {
+            Language::Japanese
+            | Language::Korean
+            | Language::SimplifiedChinese
+            | Language::TraditionalChinese
+            | Language::Unknown(_) => PluralCategory::Other,
+            Language::French => match count {
+                0 | 1 => PluralCategory::One,
+                _ => PluralCategory::Other,
+            },
+            Language::Russian => match (count % 10, count % 100) {
+                (1, n) if n != 11 => PluralCategory::One,
+                (2..=4, n) if !(12..=14).contains(&n) => PluralCategory::Few,
+                _ => PluralCategory::Many,
+            },
+            Language::English
+            | Language::German
+            | Language::Italian
+            | Language::Spanish
+            | Language::Dutch
+            | Language::Portuguese => match count {
+                1 => PluralCategory::One,
+                _ => PluralCategory::Other,
+            },
+        }
+    }
+
+    /// Returns the suffix of the keys of this category, e.g. `".one"`.
+    pub fn suffix(self) -> &'static str {
+        table::PLURAL_SUFFIXES[self as usize]
+    }
+}
+
+impl Localizer {
+    /// Creates a localizer from tables ordered by priority: the first one is the preferred language,
+    /// the following ones are the fallbacks.
+    pub fn new(tables: Vec<(Language, StringTable)>) -> Self {
+        Localizer { tables }
+    }
+
+    /// Loads the tables of `languages` from the files `{dir}/{tag}.lang`, in order of priority.
+    ///
+    /// Languages without a file are skipped, and duplicates are only loaded once.
+    pub fn load<P: AsRef<Path>>(dir: P, languages: &[Language]) -> io::Result<Self> {
+        let mut tables: Vec<(Language, StringTable)> = Vec::new();
+
+        for &language in languages {
+            if tables.iter().any(|(loaded, _)| *loaded == language) {
+                continue;
+            }
+
+            let path = dir
+                .as_ref()
+                .join(format!("{}.{TABLE_EXTENSION}", language.bcp47_tag()));
+            let text = match std::fs::read_to_string(path) {
+                Ok(text) => text,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            let table = StringTable::parse(&text)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+            tables.push((language, table));
+        }
+
+        Ok(Localizer { tables })
+    }
+
+    /// Loads the tables of the console's language, then of `fallbacks`, from `dir`.
+    ///
+    /// See [`Localizer::load`].
+    pub fn load_for_system<P: AsRef<Path>>(
+        cfgu: &Cfgu,
+        dir: P,
+        fallbacks: &[Language],
+    ) -> io::Result<Self> {
+        let language = cfgu
+            .get_language()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+        let mut languages = vec![language];
+        languages.extend_from_slice(fallbacks);
+
+        Self::load(dir, &languages)
+    }
+
+    /// Returns the languages of the loaded tables, in order of priority.
+    pub fn languages(&self) -> impl Iterator<Item = Language> + '_ {
+        self.tables.iter().map(|(language, _)| *language)
+    }
+
+    /// Returns the string of `key` in the first language that has it.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.tables.iter().find_map(|(_, table)| table.get(key))
+    }
+
+    /// Returns the string of `key` with its `{name}` placeholders replaced by `args`.
+    ///
+    /// Missing keys give the key itself, so that they're easy to spot.
+    pub fn translate(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        match self.get(key) {
+            Some(text) => interpolate(text, args),
+            None => key.to_owned(),
+        }
+    }
+
+    /// Returns the plural form of `key` matching `count`, with its placeholders replaced by `args`
+    /// and `{count}`.
+    ///
+    /// The form is looked up as `key.<category>`, then `key.other`, then `key`, in each language in turn.
+    pub fn translate_plural(&self, key: &str, count: u64, args: &[(&str, &dyn Display)]) -> String {
+        let forms = self.tables.iter().find_map(|(language, table)| {
+            let category = PluralCategory::of(*language, count);
+
+            [category, PluralCategory::Other]
+                .iter()
+                .find_map(|category| table.get(&format!("{key}{}", category.suffix())))
+                .or_else(|| table.get(key))
+        });
+
+        match forms {
+            Some(text) => {
+                let mut all_args = vec![("count", &count as &dyn Display)];
+                all_args.extend_from_slice(args);
+                interpolate(text, &all_args)
+            }
+            None => key.to_owned(),
+        }
+    }
+
+    /// Returns, for each language lacking some strings, the keys (without plural suffixes) that are
+    /// only in the tables of other languages.
+    ///
+    /// This is meant to be checked in tests, to make sure every language is complete.
+    pub fn missing_keys(&self) -> Vec<(Language, Vec<&str>)> {
+        self.tables
+            .iter()
+            .map(|(language, table)| {
+                let mut missing = self
+                    .tables
+                    .iter()
+                    .flat_map(|(_, other)| table.missing_keys(other))
+                    .collect::<Vec<_>>();
+                missing.sort_unstable();
+                missing.dedup();
+
+                (*language, missing)
+            })
+            .filter(|(_, missing)| !missing.is_empty())
+            .collect()
+    }
+}
+
+/// Replaces the `{name}` placeholders of `text` with the matching argument. `{{` and `}}` give
+/// a single brace, and unknown placeholders are left as is.
+fn interpolate(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        result.push_str(&rest[..start]);
+        let tail = &rest[start..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            result.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        let placeholder = tail
+            .find('}')
+            .filter(|_| tail.starts_with('{'))
+            .and_then(|end| {
+                let name = &tail[1..end];
+                let (_, value) = args.iter().find(|(arg, _)| *arg == name)?;
+                Some((end, value))
+            });
+
+        match placeholder {
+            Some((end, value)) => {
+                result.push_str(&value.to_string());
+                rest = &tail[end + 1..];
+            }
+            None => {
+                result.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Looks up a string in a [`Localizer`](crate::i18n::Localizer), with `name = value` arguments for its placeholders.
+///
+/// Passing `count` as the first argument picks the plural form matching it, and makes it available
+/// as the `{count}` placeholder.
+///
+/// ```no_run
+/// # use ctru::i18n::Localizer;
+/// # use ctru::tr;
+/// # let strings = Localizer::default();
+/// let title = tr!(strings, "title");
+/// let greeting = tr!(strings, "greeting", name = "Ferris");
+/// let apples = tr!(strings, "apples", count = 3, owner = "Ferris");
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($localizer:expr, $key:expr, count = $count:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $localizer.translate_plural(
+            $key,
+            $count as u64,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*],
+        )
+    };
+    ($localizer:expr, $key:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $localizer.translate(
+            $key,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn localizer() -> Localizer {
+        let french = StringTable::parse(
+            "# Test table\n\
+             greeting = Bonjour, {name} !\n\
+             apples.one = {count} pomme\n\
+             apples.other = {count} pommes\n",
+        )
+        .unwrap();
+        let english = StringTable::parse(
+            "greeting = Hello, {name}!\n\
+             apples.one = {count} apple\n\
+             apples.other = {count} apples\n\
+             quit = Press START to quit\\nor SELECT to restart\n\
+             braces = {{not a placeholder}} {unknown}\n",
+        )
+        .unwrap();
+
+        Localizer::new(vec![
+            (Language::French, french),
+            (Language::English, english),
+        ])
+    }
+
+    #[test]
+    fn lookup_and_fallback() {
+        let strings = localizer();
+
+        assert_eq!(
+            tr!(strings, "greeting", name = "Ferris"),
+            "Bonjour, Ferris !"
+        );
+        assert_eq!(
+            tr!(strings, "quit"),
+            "Press START to quit\nor SELECT to restart"
+        );
+        assert_eq!(tr!(strings, "braces"), "{not a placeholder} {unknown}");
+        assert_eq!(tr!(strings, "missing"), "missing");
+    }
+
+    #[test]
+    fn plurals() {
+        let strings = localizer();
+
+        // French uses the singular for 0 too.
+        assert_eq!(tr!(strings, "apples", count = 0), "0 pomme");
+        assert_eq!(tr!(strings, "apples", count = 2), "2 pommes");
+
+        assert_eq!(
+            PluralCategory::of(Language::English, 0),
+            PluralCategory::Other
+        );
+        assert_eq!(
+            PluralCategory::of(Language::Russian, 21),
+            PluralCategory::One
+        );
+        assert_eq!(
+            PluralCategory::of(Language::Russian, 13),
+            PluralCategory::Many
+        );
+        assert_eq!(
+            PluralCategory::of(Language::Russian, 24),
+            PluralCategory::Few
+        );
+        assert_eq!(
+            PluralCategory::of(Language::Japanese, 1),
+            PluralCategory::Other
+        );
+    }
+
+    #[test]
+    fn missing_keys() {
+        let strings = localizer();
+
+        assert_eq!(
+            strings.missing_keys(),
+            vec![(Language::French, vec!["braces", "quit"])]
+        );
+    }
+
+    #[test]
+    fn invalid_tables() {
+        assert_eq!(
+            StringTable::parse("a = 1\nno separator").unwrap_err(),
+            ParseError::MissingSeparator { line: 2 }
+        );
+        assert_eq!(
+            StringTable::parse(" = value").unwrap_err(),
+            ParseError::EmptyKey { line: 1 }
+        );
+        assert_eq!(
+            StringTable::parse("a = 1\n\na = 2").unwrap_err(),
+            ParseError::DuplicateKey {
+                line: 3,
+                key: String::from("a")
+            }
+        );
+    }
+}
@@ -25,11 +25,22 @@ static __stacksize__: usize = 2 * 1024 * 1024; // 2MB
 /// When ´test´ is enabled, this function won't do anything, as it should be overridden by the ´test´ environment.
 pub fn use_panic_handler() {
     #[cfg(not(test))]
-    panic_hook_setup();
+    panic_hook_setup(false);
+}
+
+/// Activate ´ctru-rs´' default panic handler, falling back to the system error applet.
+///
+/// This works like [use_panic_handler], but when there's no active [console::Console] to print on,
+/// the panic message is shown with the [applets::error::ErrorApplet] instead, as long as [services::Apt]
+/// and [gfx::Gfx] are initialized.
+pub fn use_panic_handler_with_error_applet() {
+    #[cfg(not(test))]
+    panic_hook_setup(true);
 }
 
 #[cfg(not(test))]
-fn panic_hook_setup() {
+fn panic_hook_setup(use_error_applet: bool) {
+    use crate::applets::error::{ErrorApplet, Kind};
     use crate::services::hid::{Hid, KeyPad};
     use std::panic::PanicInfo;
 
@@ -41,7 +52,11 @@ fn panic_hook_setup() {
         default_hook(info);
 
         // Only for panics in the main thread
-        if main_thread == std::thread::current().id() && console::Console::exists() {
+        if main_thread != std::thread::current().id() {
+            return;
+        }
+
+        if console::Console::exists() {
             println!("\nPress SELECT to exit the software");
 
             match Hid::init() {
@@ -54,6 +69,13 @@ fn panic_hook_setup() {
                 },
                 Err(e) => println!("Error while intializing Hid controller during panic: {e}"),
             }
+        } else if use_error_applet && gfx::Gfx::is_active() && unsafe { ctru_sys::aptIsActive() } {
+            let mut applet = ErrorApplet::new(Kind::Text);
+            applet.set_word_wrap(true);
+            applet.set_text(&info.to_string());
+
+            // Safety: we just checked that APT and the graphics are running.
+            unsafe { applet.launch_unchecked() };
         }
     });
     std::panic::set_hook(new_hook);
@@ -63,6 +85,7 @@ pub mod applets;
 pub mod console;
 pub mod error;
 pub mod gfx;
+pub mod i18n;
 pub mod linear;
 pub mod mii;
 pub mod prelude;
@@ -0,0 +1,397 @@
+//! Switch Mii format
+//!
+//! This module contains [`CharInfo`], the representation of a Mii used by the Switch (`nn::mii::CharInfo`),
+//! and its conversions from and to [`MiiData`].
+//!
+//! The Switch has more colours and glasses than the 3DS: when converting to [`MiiData`], the ones the 3DS
+//! doesn't have fall back to the default one.
+//!
+//! The Switch stores its Miis in a packed format, see [`storedata`](super::storedata).
+
+use super::{
+    encode_name, utf16_byte_pairs_to_string, Charset, MiiData, MiiDataError, MiiSex, OriginConsole,
+    MII_ID_RANGE,
+};
+
+/// Switch colours matching the 3DS hair colours (also used for the eyebrows and the beard).
+pub const HAIR_COLORS: [u8; 8] = [8, 1, 2, 3, 4, 5, 6, 7];
+/// Switch colours matching the 3DS eye colours.
+pub const EYE_COLORS: [u8; 6] = [8, 9, 10, 11, 12, 13];
+/// Switch colours matching the 3DS mouth colours.
+pub const MOUTH_COLORS: [u8; 5] = [19, 20, 21, 22, 23];
+/// Switch colours matching the 3DS glasses colours.
+pub const GLASS_COLORS: [u8; 6] = [8, 14, 15, 16, 17, 18];
+/// Switch skin colours matching the 3DS ones.
+pub const FACELINE_COLORS: [u8; 6] = [0, 1, 2, 3, 4, 5];
+/// Number of glasses styles available on the 3DS.
+const GLASS_TYPE_COUNT: u8 = 9;
+
+/// A Mii in the Switch format
+///
+/// Fields are named after the Switch SDK, and hold the raw values of the format.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CharInfo {
+    pub create_id: [u8; 16],
+    pub nickname: String,
+    pub font_region: u8,
+    pub favorite_color: u8,
+    pub gender: u8,
+    pub height: u8,
+    pub build: u8,
+    pub mii_type: u8,
+    pub region_move: u8,
+    pub faceline_type: u8,
+    pub faceline_color: u8,
+    pub faceline_wrinkle: u8,
+    pub faceline_make: u8,
+    pub hair_type: u8,
+    pub hair_color: u8,
+    pub hair_flip: u8,
+    pub eye_type: u8,
+    pub eye_color: u8,
+    pub eye_scale: u8,
+    pub eye_aspect: u8,
+    pub eye_rotate: u8,
+    pub eye_x: u8,
+    pub eye_y: u8,
+    pub eyebrow_type: u8,
+    pub eyebrow_color: u8,
+    pub eyebrow_scale: u8,
+    pub eyebrow_aspect: u8,
+    pub eyebrow_rotate: u8,
+    pub eyebrow_x: u8,
+    pub eyebrow_y: u8,
+    pub nose_type: u8,
+    pub nose_scale: u8,
+    pub nose_y: u8,
+    pub mouth_type: u8,
+    pub mouth_color: u8,
+    pub mouth_scale: u8,
+    pub mouth_aspect: u8,
+    pub mouth_y: u8,
+    pub beard_color: u8,
+    pub beard_type: u8,
+    pub mustache_type: u8,
+    pub mustache_scale: u8,
+    pub mustache_y: u8,
+    pub glass_type: u8,
+    pub glass_color: u8,
+    pub glass_scale: u8,
+    pub glass_y: u8,
+    pub mole_type: u8,
+    pub mole_scale: u8,
+    pub mole_x: u8,
+    pub mole_y: u8,
+}
+
+impl CharInfo {
+    /// Size of the raw data.
+    pub const SIZE: usize = 0x58;
+
+    /// Decodes a Mii from its raw data.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MiiDataError> {
+        if data.len() != Self::SIZE {
+            return Err(MiiDataError::InvalidSize(data.len()));
+        }
+
+        let mut create_id = [0; 16];
+        create_id.copy_from_slice(&data[..NICKNAME_OFFSET]);
+        let nickname = utf16_byte_pairs_to_string(&data[NICKNAME_OFFSET..FIELDS_OFFSET]);
+
+        let mut info = CharInfo {
+            create_id,
+            nickname,
+            ..Default::default()
+        };
+        for (field, &value) in info.fields_mut().into_iter().zip(&data[FIELDS_OFFSET..]) {
+            *field = value;
+        }
+
+        Ok(info)
+    }
+
+    /// Encodes the Mii to its raw data.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut data = [0; Self::SIZE];
+
+        data[..NICKNAME_OFFSET].copy_from_slice(&self.create_id);
+        // Leave room for the NUL terminator.
+        encode_name(
+            &mut data[NICKNAME_OFFSET..FIELDS_OFFSET - 2],
+            &self.nickname,
+        );
+
+        let mut info = self.clone();
+        for (byte, field) in data[FIELDS_OFFSET..].iter_mut().zip(info.fields_mut()) {
+            *byte = *field;
+        }
+
+        data
+    }
+
+    /// Returns the one-byte fields, in the order of the raw data.
+    fn fields_mut(&mut self) -> [&mut u8; FIELD_COUNT] {
+        [
+            &mut self.font_region,
+            &mut self.favorite_color,
+            &mut self.gender,
+            &mut self.height,
+            &mut self.build,
+            &mut self.mii_type,
+            &mut self.region_move,
+            &mut self.faceline_type,
+            &mut self.faceline_color,
+            &mut self.faceline_wrinkle,
+            &mut self.faceline_make,
+            &mut self.hair_type,
+            &mut self.hair_color,
+            &mut self.hair_flip,
+            &mut self.eye_type,
+            &mut self.eye_color,
+            &mut self.eye_scale,
+            &mut self.eye_aspect,
+            &mut self.eye_rotate,
+            &mut self.eye_x,
+            &mut self.eye_y,
+            &mut self.eyebrow_type,
+            &mut self.eyebrow_color,
+            &mut self.eyebrow_scale,
+            &mut self.eyebrow_aspect,
+            &mut self.eyebrow_rotate,
+            &mut self.eyebrow_x,
+            &mut self.eyebrow_y,
+            &mut self.nose_type,
+            &mut self.nose_scale,
+            &mut self.nose_y,
+            &mut self.mouth_type,
+            &mut self.mouth_color,
+            &mut self.mouth_scale,
+            &mut self.mouth_aspect,
+            &mut self.mouth_y,
+            &mut self.beard_color,
+            &mut self.beard_type,
+            &mut self.mustache_type,
+            &mut self.mustache_scale,
+            &mut self.mustache_y,
+            &mut self.glass_type,
+            &mut self.glass_color,
+            &mut self.glass_scale,
+            &mut self.glass_y,
+            &mut self.mole_type,
+            &mut self.mole_scale,
+            &mut self.mole_x,
+            &mut self.mole_y,
+        ]
+    }
+}
+
+impl From<&MiiData> for CharInfo {
+    fn from(mii: &MiiData) -> Self {
+        CharInfo {
+            create_id: create_id(mii),
+            nickname: mii.name.clone(),
+            font_region: mii.options.charset as u8,
+            favorite_color: mii.details.shirt_color,
+            gender: mii.details.sex as u8,
+            height: mii.height,
+            build: mii.width,
+            mii_type: 0,
+            region_move: 0,
+            faceline_type: mii.face_details.style.shape,
+            faceline_color: to_switch_color(&FACELINE_COLORS, mii.face_details.style.skin_color),
+            faceline_wrinkle: mii.face_details.wrinkles,
+            faceline_make: mii.face_details.makeup,
+            hair_type: mii.hair_details.style,
+            hair_color: to_switch_color(&HAIR_COLORS, mii.hair_details.color),
+            hair_flip: mii.hair_details.is_flipped.into(),
+            eye_type: mii.eye_details.style,
+            eye_color: to_switch_color(&EYE_COLORS, mii.eye_details.color),
+            eye_scale: mii.eye_details.scale,
+            eye_aspect: mii.eye_details.y_scale,
+            eye_rotate: mii.eye_details.rotation,
+            eye_x: mii.eye_details.x_spacing,
+            eye_y: mii.eye_details.y_position,
+            eyebrow_type: mii.eyebrow_details.style,
+            eyebrow_color: to_switch_color(&HAIR_COLORS, mii.eyebrow_details.color),
+            eyebrow_scale: mii.eyebrow_details.scale,
+            eyebrow_aspect: mii.eyebrow_details.y_scale,
+            eyebrow_rotate: mii.eyebrow_details.rotation,
+            eyebrow_x: mii.eyebrow_details.x_spacing,
+            eyebrow_y: mii.eyebrow_details.y_position,
+            nose_type: mii.nose_details.style,
+            nose_scale: mii.nose_details.scale,
+            nose_y: mii.nose_details.y_position,
+            mouth_type: mii.mouth_details.style,
+            mouth_color: to_switch_color(&MOUTH_COLORS, mii.mouth_details.color),
+            mouth_scale: mii.mouth_details.scale,
+            mouth_aspect: mii.mouth_details.y_scale,
+            mouth_y: mii.mustache_details.mouth_y_position,
+            beard_color: to_switch_color(&HAIR_COLORS, mii.beard_details.color),
+            beard_type: mii.beard_details.style,
+            mustache_type: mii.mustache_details.mustache_style,
+            // The 3DS stores the mustache scale and position along with the beard.
+            mustache_scale: mii.beard_details.scale,
+            mustache_y: mii.beard_details.y_position,
+            glass_type: mii.glass_details.style,
+            glass_color: to_switch_color(&GLASS_COLORS, mii.glass_details.color),
+            glass_scale: mii.glass_details.scale,
+            glass_y: mii.glass_details.y_position,
+            mole_type: mii.mole_details.is_enabled.into(),
+            mole_scale: mii.mole_details.scale,
+            mole_x: mii.mole_details.x_position,
+            mole_y: mii.mole_details.y_position,
+        }
+    }
+}
+
+impl From<&CharInfo> for MiiData {
+    fn from(info: &CharInfo) -> Self {
+        let mut mii = MiiData::blank();
+
+        mii.options.is_copying_allowed = true;
+        mii.options.charset = match info.font_region {
+            1 => Charset::China,
+            2 => Charset::Korea,
+            3 => Charset::Taiwan,
+            _ => Charset::JapanUSAEurope,
+        };
+        mii.console_identity.origin_console = OriginConsole::WiiUSwitch;
+        mii.name = info.nickname.clone();
+
+        mii.details.sex = match info.gender {
+            0 => MiiSex::Male,
+            _ => MiiSex::Female,
+        };
+        mii.details.shirt_color = info.favorite_color;
+        mii.height = info.height;
+        mii.width = info.build;
+
+        mii.face_details.style.shape = info.faceline_type;
+        mii.face_details.style.skin_color = to_3ds_color(&FACELINE_COLORS, info.faceline_color);
+        mii.face_details.wrinkles = info.faceline_wrinkle;
+        mii.face_details.makeup = info.faceline_make;
+
+        mii.hair_details.style = info.hair_type;
+        mii.hair_details.color = to_3ds_color(&HAIR_COLORS, info.hair_color);
+        mii.hair_details.is_flipped = info.hair_flip != 0;
+
+        mii.eye_details.style = info.eye_type;
+        mii.eye_details.color = to_3ds_color(&EYE_COLORS, info.eye_color);
+        mii.eye_details.scale = info.eye_scale;
+        mii.eye_details.y_scale = info.eye_aspect;
+        mii.eye_details.rotation = info.eye_rotate;
+        mii.eye_details.x_spacing = info.eye_x;
+        mii.eye_details.y_position = info.eye_y;
+
+        mii.eyebrow_details.style = info.eyebrow_type;
+        mii.eyebrow_details.color = to_3ds_color(&HAIR_COLORS, info.eyebrow_color);
+        mii.eyebrow_details.scale = info.eyebrow_scale;
+        mii.eyebrow_details.y_scale = info.eyebrow_aspect;
+        mii.eyebrow_details.rotation = info.eyebrow_rotate;
+        mii.eyebrow_details.x_spacing = info.eyebrow_x;
+        mii.eyebrow_details.y_position = info.eyebrow_y;
+
+        mii.nose_details.style = info.nose_type;
+        mii.nose_details.scale = info.nose_scale;
+        mii.nose_details.y_position = info.nose_y;
+
+        mii.mouth_details.style = info.mouth_type;
+        mii.mouth_details.color = to_3ds_color(&MOUTH_COLORS, info.mouth_color);
+        mii.mouth_details.scale = info.mouth_scale;
+        mii.mouth_details.y_scale = info.mouth_aspect;
+        mii.mustache_details.mouth_y_position = info.mouth_y;
+
+        mii.mustache_details.mustache_style = info.mustache_type;
+        mii.beard_details.style = info.beard_type;
+        mii.beard_details.color = to_3ds_color(&HAIR_COLORS, info.beard_color);
+        mii.beard_details.scale = info.mustache_scale;
+        mii.beard_details.y_position = info.mustache_y;
+
+        if info.glass_type < GLASS_TYPE_COUNT {
+            mii.glass_details.style = info.glass_type;
+        }
+        mii.glass_details.color = to_3ds_color(&GLASS_COLORS, info.glass_color);
+        mii.glass_details.scale = info.glass_scale;
+        mii.glass_details.y_position = info.glass_y;
+
+        mii.mole_details.is_enabled = info.mole_type != 0;
+        mii.mole_details.scale = info.mole_scale;
+        mii.mole_details.x_position = info.mole_x;
+        mii.mole_details.y_position = info.mole_y;
+
+        mii
+    }
+}
+
+const NICKNAME_OFFSET: usize = 0x10;
+const FIELDS_OFFSET: usize = 0x26;
+const FIELD_COUNT: usize = 49;
+
+/// Maps a 3DS colour to the Switch one, using one of the tables above.
+fn to_switch_color(table: &[u8], color: u8) -> u8 {
+    table.get(usize::from(color)).copied().unwrap_or(table[0])
+}
+
+/// Maps a Switch colour to the 3DS one, falling back to the default colour if the 3DS doesn't have it.
+fn to_3ds_color(table: &[u8], color: u8) -> u8 {
+    table.iter().position(|&c| c == color).unwrap_or(0) as u8
+}
+
+/// Builds a stable create ID (a version 4 UUID) from the IDs of a 3DS Mii.
+fn create_id(mii: &MiiData) -> [u8; 16] {
+    let mut id = [0; 16];
+    id[..8].copy_from_slice(&mii.system_id);
+    id[8..12].copy_from_slice(&mii.raw[MII_ID_RANGE]);
+    id[12..].copy_from_slice(&mii.mac_address[2..]);
+
+    id[6] = (id[6] & 0x0F) | 0x40;
+    id[8] = (id[8] & 0x3F) | 0x80;
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mii::tests::FERRIS;
+
+    #[test]
+    fn switch_round_trip() {
+        let mii = MiiData::from_bytes(&FERRIS).unwrap();
+        let info = CharInfo::from(&mii);
+        assert_eq!(info.nickname, "Ferris");
+        assert_eq!(info.gender, 1);
+        assert_eq!(info.create_id[6] >> 4, 4);
+
+        let bytes = info.to_bytes();
+        assert_eq!(CharInfo::from_bytes(&bytes).unwrap(), info);
+
+        let converted = MiiData::from(&info);
+        assert_eq!(converted.name, mii.name);
+        assert_eq!(converted.details.sex, mii.details.sex);
+        assert_eq!(converted.hair_details.color, mii.hair_details.color);
+        assert_eq!(converted.eye_details.color, mii.eye_details.color);
+        assert_eq!(converted.beard_details.scale, mii.beard_details.scale);
+        assert_eq!(
+            converted.mustache_details.mouth_y_position,
+            mii.mustache_details.mouth_y_position
+        );
+        assert_eq!(
+            converted.mole_details.y_position,
+            mii.mole_details.y_position
+        );
+        assert_eq!(CharInfo::from(&converted).nickname, "Ferris");
+    }
+
+    #[test]
+    fn switch_only_features_fall_back() {
+        let info = CharInfo {
+            hair_color: 42,
+            glass_type: 12,
+            ..Default::default()
+        };
+
+        let mii = MiiData::from(&info);
+        assert_eq!(mii.hair_details.color, 0);
+        assert_eq!(mii.glass_details.style, 0);
+    }
+}
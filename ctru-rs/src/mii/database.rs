@@ -0,0 +1,107 @@
+//! Mii database
+//!
+//! The 3DS stores the Miis made with Mii Maker in a database, `CFL_DB.dat`. [`MiiDatabase`] parses the
+//! content of that file, which can be read with [`crate::services::fs`], to list every Mii at once
+//! instead of asking the user to pick them one by one with the
+//! [``MiiSelector``](crate::applets::mii_selector::MiiSelector).
+//!
+//! Source for the layout: <https://www.3dbrew.org/wiki/Mii_Maker>
+
+use super::{MiiData, MiiDataError};
+
+/// The Miis stored in a `CFL_DB.dat` file
+#[derive(Clone, Debug)]
+pub struct MiiDatabase {
+    miis: Vec<(usize, MiiData)>,
+}
+
+impl MiiDatabase {
+    /// Magic value at the start of the database.
+    pub const MAGIC: [u8; 4] = *b"CFOG";
+    /// Number of slots for Miis in the database.
+    pub const SLOT_COUNT: usize = 100;
+    /// Minimum size of the database, up to the end of the Mii slots.
+    pub const MIN_SIZE: usize = SLOTS_OFFSET + Self::SLOT_COUNT * MiiData::SIZE;
+
+    /// Parses the content of a `CFL_DB.dat` file.
+    ///
+    /// Only the header and the Mii slots are read, so `data` can be the whole file or just
+    /// its first [`MiiDatabase::MIN_SIZE`] bytes. Empty slots are skipped.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MiiDataError> {
+        if data.len() < Self::MIN_SIZE {
+            return Err(MiiDataError::InvalidSize(data.len()));
+        }
+        if data[..4] != Self::MAGIC {
+            return Err(MiiDataError::InvalidMagic);
+        }
+
+        let miis = data[SLOTS_OFFSET..Self::MIN_SIZE]
+            .chunks_exact(MiiData::SIZE)
+            .enumerate()
+            .filter(|(_, slot)| slot.iter().any(|&byte| byte != 0))
+            .map(|(index, slot)| Ok((index, MiiData::from_bytes(slot)?)))
+            .collect::<Result<_, MiiDataError>>()?;
+
+        Ok(MiiDatabase { miis })
+    }
+
+    /// Returns the number of Miis in the database.
+    pub fn len(&self) -> usize {
+        self.miis.len()
+    }
+
+    /// Returns `true` if the database holds no Mii.
+    pub fn is_empty(&self) -> bool {
+        self.miis.is_empty()
+    }
+
+    /// Returns the Mii stored in the slot `index`, if any.
+    pub fn get(&self, index: usize) -> Option<&MiiData> {
+        self.miis
+            .iter()
+            .find(|(slot, _)| *slot == index)
+            .map(|(_, mii)| mii)
+    }
+
+    /// Returns an iterator over the Miis of the database, along with the index of their slot.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &MiiData)> {
+        self.miis.iter().map(|(slot, mii)| (*slot, mii))
+    }
+
+    /// Returns the Miis of the database, in the order of their slots.
+    pub fn into_miis(self) -> Vec<MiiData> {
+        self.miis.into_iter().map(|(_, mii)| mii).collect()
+    }
+}
+
+const SLOTS_OFFSET: usize = 0x8;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mii::tests::FERRIS;
+
+    #[test]
+    fn parse_database() {
+        let mut data = vec![0; MiiDatabase::MIN_SIZE + 0x10];
+        data[..4].copy_from_slice(b"CFOG");
+        let slot = 0x8 + 3 * MiiData::SIZE;
+        data[slot..slot + MiiData::SIZE].copy_from_slice(&FERRIS[..MiiData::SIZE]);
+
+        let db = MiiDatabase::from_bytes(&data).unwrap();
+        assert_eq!(db.len(), 1);
+        assert_eq!(db.get(3).unwrap().name, "Ferris");
+        assert!(db.get(0).is_none());
+        assert_eq!(db.iter().next().unwrap().0, 3);
+
+        assert_eq!(
+            MiiDatabase::from_bytes(&data[..0x100]).unwrap_err(),
+            MiiDataError::InvalidSize(0x100)
+        );
+        data[0] = b'X';
+        assert_eq!(
+            MiiDatabase::from_bytes(&data).unwrap_err(),
+            MiiDataError::InvalidMagic
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Mii Maker values
+//!
+//! The values stored in [`MiiData`](super::MiiData) for some features don't follow the order in which
+//! Mii Maker shows them. This module maps those values from and to their position in the editor.
+//!
+//! Source for the tables: <https://www.3dbrew.org/wiki/Mii#Mapped_Editor_.3C-.3E_Hex_values>
+
+/// Number of choices shown on each page of the editor.
+pub const SLOTS_PER_PAGE: u8 = 12;
+
+/// A feature whose values are shuffled in the editor
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Feature {
+    /// [`FaceStyle::shape`](super::FaceStyle::shape)
+    FaceShape,
+    /// [`HairDetails::style`](super::HairDetails::style)
+    Hair,
+    /// [`EyebrowDetails::style`](super::EyebrowDetails::style)
+    Eyebrow,
+    /// [`EyeDetails::style`](super::EyeDetails::style)
+    Eye,
+    /// [`NoseDetails::style`](super::NoseDetails::style)
+    Nose,
+    /// [`MouthDetails::style`](super::MouthDetails::style)
+    Mouth,
+}
+
+/// Represents the position of a choice in the editor
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct EditorPosition {
+    pub page_index: u8,
+    pub slot_index: u8,
+}
+
+impl Feature {
+    /// Returns the position in the editor of the stored `value`, or `None` if the value is out of range.
+    pub fn position(self, value: u8) -> Option<EditorPosition> {
+        let index = self.table().iter().position(|&v| v == value)? as u8;
+
+        Some(EditorPosition {
+            page_index: index / SLOTS_PER_PAGE,
+            slot_index: index % SLOTS_PER_PAGE,
+        })
+    }
+
+    /// Returns the value stored for the choice at `position` in the editor, or `None` if there's no such choice.
+    pub fn value(self, position: EditorPosition) -> Option<u8> {
+        if position.slot_index >= SLOTS_PER_PAGE {
+            return None;
+        }
+
+        let index = usize::from(position.page_index) * usize::from(SLOTS_PER_PAGE)
+            + usize::from(position.slot_index);
+        self.table().get(index).copied()
+    }
+
+    /// Returns the stored values, in the order of the editor.
+    pub fn table(self) -> &'static [u8] {
+        match self {
+            Feature::FaceShape => &FACE_SHAPES,
+            Feature::Hair => &HAIRS,
+            Feature::Eyebrow => &EYEBROWS,
+            Feature::Eye => &EYES,
+            Feature::Nose => &NOSES,
+            Feature::Mouth => &MOUTHS,
+        }
+    }
+}
+
+#[rustfmt::skip]
+const FACE_SHAPES: [u8; 12] = [
+    0x00, 0x01, 0x08, 0x02, 0x03, 0x09, 0x04, 0x05, 0x0A, 0x06, 0x07, 0x0B,
+];
+
+#[rustfmt::skip]
+const HAIRS: [u8; 132] = [
+    0x21, 0x2F, 0x28, 0x25, 0x20, 0x6B, 0x30, 0x33, 0x37, 0x46, 0x2C, 0x42,
+    0x34, 0x32, 0x26, 0x31, 0x2B, 0x1F, 0x38, 0x44, 0x3E, 0x73, 0x4C, 0x77,
+    0x40, 0x51, 0x74, 0x79, 0x16, 0x3A, 0x3C, 0x57, 0x7D, 0x75, 0x49, 0x4B,
+    0x2A, 0x59, 0x39, 0x36, 0x50, 0x22, 0x17, 0x56, 0x58, 0x76, 0x27, 0x24,
+    0x2D, 0x43, 0x3B, 0x41, 0x29, 0x1E, 0x0C, 0x10, 0x0A, 0x52, 0x80, 0x81,
+    0x0E, 0x5F, 0x69, 0x64, 0x06, 0x14, 0x5D, 0x66, 0x1B, 0x04, 0x11, 0x6E,
+    0x7B, 0x08, 0x6A, 0x48, 0x03, 0x15, 0x00, 0x62, 0x3F, 0x5A, 0x0B, 0x78,
+    0x05, 0x4A, 0x6C, 0x5E, 0x7C, 0x19, 0x63, 0x45, 0x23, 0x0D, 0x7A, 0x71,
+    0x35, 0x18, 0x55, 0x53, 0x47, 0x83, 0x60, 0x65, 0x1D, 0x07, 0x0F, 0x70,
+    0x4F, 0x01, 0x6D, 0x7F, 0x5B, 0x1A, 0x3D, 0x67, 0x02, 0x4D, 0x12, 0x5C,
+    0x54, 0x09, 0x13, 0x82, 0x61, 0x68, 0x2E, 0x4E, 0x1C, 0x72, 0x7E, 0x6F,
+];
+
+#[rustfmt::skip]
+const EYEBROWS: [u8; 24] = [
+    0x06, 0x00, 0x0C, 0x01, 0x09, 0x13, 0x07, 0x15, 0x08, 0x11, 0x05, 0x04,
+    0x0B, 0x0A, 0x02, 0x03, 0x0E, 0x14, 0x0F, 0x0D, 0x16, 0x12, 0x10, 0x17,
+];
+
+#[rustfmt::skip]
+const EYES: [u8; 60] = [
+    0x02, 0x04, 0x00, 0x08, 0x27, 0x11, 0x01, 0x1A, 0x10, 0x0F, 0x1B, 0x14,
+    0x21, 0x0B, 0x13, 0x20, 0x09, 0x0C, 0x17, 0x22, 0x15, 0x19, 0x28, 0x23,
+    0x05, 0x29, 0x0D, 0x24, 0x25, 0x06, 0x18, 0x1E, 0x1F, 0x12, 0x1C, 0x2E,
+    0x07, 0x2C, 0x26, 0x2A, 0x2D, 0x1D, 0x03, 0x2B, 0x16, 0x0A, 0x0E, 0x2F,
+    0x30, 0x31, 0x32, 0x35, 0x3B, 0x38, 0x36, 0x3A, 0x39, 0x37, 0x33, 0x34,
+];
+
+#[rustfmt::skip]
+const NOSES: [u8; 18] = [
+    0x01, 0x0A, 0x02, 0x03, 0x06, 0x00, 0x05, 0x04, 0x08, 0x09, 0x07, 0x0B,
+    0x0D, 0x0E, 0x0C, 0x11, 0x10, 0x0F,
+];
+
+#[rustfmt::skip]
+const MOUTHS: [u8; 36] = [
+    0x17, 0x01, 0x13, 0x15, 0x16, 0x05, 0x00, 0x08, 0x0A, 0x10, 0x06, 0x0D,
+    0x07, 0x09, 0x02, 0x11, 0x03, 0x04, 0x0F, 0x0B, 0x14, 0x12, 0x0E, 0x0C,
+    0x1B, 0x1E, 0x18, 0x19, 0x1D, 0x1C, 0x1A, 0x23, 0x1F, 0x22, 0x21, 0x20,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn editor_tables() {
+        for feature in [
+            Feature::FaceShape,
+            Feature::Hair,
+            Feature::Eyebrow,
+            Feature::Eye,
+            Feature::Nose,
+            Feature::Mouth,
+        ] {
+            // Every value appears exactly once.
+            let mut values = feature.table().to_vec();
+            values.sort_unstable();
+            assert!(values.iter().enumerate().all(|(i, &v)| usize::from(v) == i));
+
+            for &value in feature.table() {
+                let position = feature.position(value).unwrap();
+                assert_eq!(feature.value(position), Some(value));
+            }
+        }
+
+        let position = EditorPosition {
+            page_index: 0,
+            slot_index: 0,
+        };
+        assert_eq!(Feature::Hair.value(position), Some(0x21));
+        assert_eq!(
+            Feature::FaceShape.position(0x08),
+            Some(EditorPosition {
+                page_index: 0,
+                slot_index: 2
+            })
+        );
+        assert_eq!(Feature::Nose.position(0x20), None);
+    }
+}
@@ -0,0 +1,852 @@
+//! Mii Data
+//!
+//! This module contains the structs that represent all the data of a Mii.
+//! This data is given by the [``MiiSelector``](crate::applets::mii_selector::MiiSelector)
+//!
+//! Miis can also be converted from and to their raw representation, to be stored or shared:
+//!
+//! - The 3DS and the Wii U share the same format: [`MiiData::to_bytes`] gives the 0x5C bytes "CFSD" data,
+//!   and [`MiiData::to_bytes_with_checksum`] the 0x60 bytes "FFSD" data, which ends with a checksum.
+//! - The Switch uses [`charinfo::CharInfo`], and packs it as [`storedata`] to store it.
+//! - Mii Studio uses an obfuscated hex string, see [`studio`].
+//!
+//! All the Miis of the console can be read at once from its database, see [`database`].
+
+pub mod charinfo;
+pub mod database;
+pub mod editor;
+pub mod storedata;
+pub mod studio;
+
+/// Represents the region lock of the console
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RegionLock {
+    None,
+    Japan,
+    USA,
+    Europe,
+}
+
+/// Represent the charset of the console
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Charset {
+    JapanUSAEurope,
+    China,
+    Korea,
+    Taiwan,
+}
+
+/// Represents the options of the Mii
+#[derive(Copy, Clone, Debug)]
+pub struct MiiDataOptions {
+    pub is_copying_allowed: bool,
+    pub is_profanity_flag_enabled: bool,
+    pub region_lock: RegionLock,
+    pub charset: Charset,
+}
+
+/// Represents the position that the Mii has on the selector
+#[derive(Copy, Clone, Debug)]
+pub struct SelectorPosition {
+    pub page_index: u8,
+    pub slot_index: u8,
+}
+
+/// Represents the kind of origin console
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OriginConsole {
+    Wii,
+    DSi,
+    /// Both New 3DS and Old 3DS
+    N3DS,
+    WiiUSwitch,
+}
+
+/// Represents the identity of the origin console
+#[derive(Copy, Clone, Debug)]
+pub struct ConsoleIdentity {
+    pub origin_console: OriginConsole,
+}
+
+/// Represents the sex of the Mii
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MiiSex {
+    Male,
+    Female,
+}
+
+/// Represents the details of the Mii
+#[derive(Copy, Clone, Debug)]
+pub struct Details {
+    pub sex: MiiSex,
+    pub birthday_month: u8,
+    pub birthday_day: u8,
+    pub shirt_color: u8,
+    pub is_favorite: bool,
+}
+
+/// Represents the face style of the Mii
+#[derive(Copy, Clone, Debug)]
+pub struct FaceStyle {
+    pub is_sharing_enabled: bool,
+    pub shape: u8,
+    pub skin_color: u8,
+}
+
+/// Represents the face details of the Mii
+#[derive(Copy, Clone, Debug)]
+pub struct FaceDetails {
+    pub style: FaceStyle,
+    pub wrinkles: u8,
+    pub makeup: u8,
+}
+
+/// Represents the hair details of the Mii
+#[derive(Copy, Clone, Debug)]
+pub struct HairDetails {
+    pub style: u8,
+    pub color: u8,
+    pub is_flipped: bool,
+}
+
+/// Represents the eye details of the Mii
+#[derive(Copy, Clone, Debug)]
+pub struct EyeDetails {
+    pub style: u8,
+    pub color: u8,
+    pub scale: u8,
+    pub y_scale: u8,
+    pub rotation: u8,
+    /// Spacing between the eyes
+    pub x_spacing: u8,
+    pub y_position: u8,
+}
+
+/// Represents the eyebrow details of the Mii
+#[derive(Copy, Clone, Debug)]
+pub struct EyebrowDetails {
+    pub style: u8,
+    pub color: u8,
+    pub scale: u8,
+    pub y_scale: u8,
+    pub rotation: u8,
+    /// Spacing between the eyebrows
+    pub x_spacing: u8,
+    pub y_position: u8,
+}
+
+/// Represents the details of the nose
+#[derive(Copy, Clone, Debug)]
+pub struct NoseDetails {
+    pub style: u8,
+    pub scale: u8,
+    pub y_position: u8,
+}
+
+/// Represents the details of the mouth
+#[derive(Copy, Clone, Debug)]
+pub struct MouthDetails {
+    pub style: u8,
+    pub color: u8,
+    pub scale: u8,
+    pub y_scale: u8,
+}
+
+/// Represents the details of the mustache
+#[derive(Copy, Clone, Debug)]
+pub struct MustacheDetails {
+    pub mouth_y_position: u8,
+    pub mustache_style: u8,
+}
+
+/// Represents the details of the beard
+#[derive(Copy, Clone, Debug)]
+pub struct BeardDetails {
+    pub style: u8,
+    pub color: u8,
+    pub scale: u8,
+    pub y_position: u8,
+}
+
+/// Represents the details of the glass
+#[derive(Copy, Clone, Debug)]
+pub struct GlassDetails {
+    pub style: u8,
+    pub color: u8,
+    pub scale: u8,
+    pub y_position: u8,
+}
+
+/// Represents the details of the mole
+#[derive(Copy, Clone, Debug)]
+pub struct MoleDetails {
+    pub is_enabled: bool,
+    pub scale: u8,
+    pub x_position: u8,
+    pub y_position: u8,
+}
+
+/// Represents all the data of a Mii
+///
+/// Some values are not ordered _like_ the Mii Editor UI. They can be mapped to their position in the editor
+/// with [`editor::Feature`].
+///
+/// The bitfields are decoded as documented on [3dbrew](https://www.3dbrew.org/wiki/Mii): least significant
+/// bit first. Up to version 0.7.1, their bits were read in reverse order, so most values (birthday, selector
+/// position, face and hair details...) were wrong and differ from the ones decoded now.
+///
+/// This struct is returned by the [``MiiSelector``](crate::applets::mii_selector::MiiSelector)
+#[derive(Clone, Debug)]
+pub struct MiiData {
+    pub options: MiiDataOptions,
+    pub selector_position: SelectorPosition,
+    pub console_identity: ConsoleIdentity,
+
+    /// Unique system ID, not dependant on the MAC address
+    pub system_id: [u8; 8],
+    pub mac_address: [u8; 6],
+
+    pub details: Details,
+    pub name: String,
+
+    pub height: u8,
+    pub width: u8,
+
+    pub face_details: FaceDetails,
+    pub hair_details: HairDetails,
+    pub eye_details: EyeDetails,
+    pub eyebrow_details: EyebrowDetails,
+    pub nose_details: NoseDetails,
+    pub mouth_details: MouthDetails,
+    pub mustache_details: MustacheDetails,
+    pub beard_details: BeardDetails,
+    pub glass_details: GlassDetails,
+    pub mole_details: MoleDetails,
+
+    pub author_name: String,
+
+    /// The raw data this Mii was decoded from, holding the bits not exposed by the fields above.
+    raw: [u8; RAW_SIZE],
+}
+
+/// Error type for the decoding of raw Mii data
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MiiDataError {
+    /// The data doesn't have the size expected for its format.
+    InvalidSize(usize),
+    /// The checksum of the data doesn't match its content.
+    InvalidChecksum,
+    /// The data isn't properly encoded, e.g. a Mii Studio string with non-hex characters.
+    InvalidEncoding,
+    /// The data doesn't start with the magic value of its format.
+    InvalidMagic,
+}
+
+impl MiiData {
+    /// Size of the raw Mii data, without checksum.
+    pub const SIZE: usize = 0x5C;
+    /// Size of the raw Mii data followed by its checksum, as found in QR codes and dumps.
+    pub const SIZE_WITH_CHECKSUM: usize = 0x60;
+
+    /// Decodes a Mii from its raw data.
+    ///
+    /// `data` can be [`MiiData::SIZE`] bytes long, or [`MiiData::SIZE_WITH_CHECKSUM`] bytes long,
+    /// in which case the checksum is verified.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MiiDataError> {
+        let mut raw = [0; RAW_SIZE];
+
+        match data.len() {
+            Self::SIZE => raw[..Self::SIZE].copy_from_slice(data),
+            Self::SIZE_WITH_CHECKSUM => {
+                let checksum = u16::from_be_bytes([data[RAW_SIZE], data[RAW_SIZE + 1]]);
+                if crc16_ccitt(&data[..RAW_SIZE]) != checksum {
+                    return Err(MiiDataError::InvalidChecksum);
+                }
+                raw.copy_from_slice(&data[..RAW_SIZE]);
+            }
+            size => return Err(MiiDataError::InvalidSize(size)),
+        }
+
+        Ok(Self::decode(raw))
+    }
+
+    /// Encodes the Mii to its raw data.
+    ///
+    /// Decoding and encoding a Mii without changing it gives back the same bytes. Values too big
+    /// for their field are truncated.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut data = [0; Self::SIZE];
+        data.copy_from_slice(&self.encode()[..Self::SIZE]);
+        data
+    }
+
+    /// Encodes the Mii to its raw data, followed by its checksum.
+    pub fn to_bytes_with_checksum(&self) -> [u8; Self::SIZE_WITH_CHECKSUM] {
+        let raw = self.encode();
+
+        let mut data = [0; Self::SIZE_WITH_CHECKSUM];
+        data[..RAW_SIZE].copy_from_slice(&raw);
+        data[RAW_SIZE..].copy_from_slice(&crc16_ccitt(&raw).to_be_bytes());
+        data
+    }
+
+    /// Returns `true` if both values describe the same Mii, i.e. made on the same console with the same ID,
+    /// even if one of them was edited since.
+    pub fn is_same_mii(&self, other: &MiiData) -> bool {
+        self.system_id == other.system_id && self.raw[MII_ID_RANGE] == other.raw[MII_ID_RANGE]
+    }
+
+    /// Returns a Mii with every field set to 0, used as a base by the conversions from other formats.
+    fn blank() -> Self {
+        let mut raw = [0; RAW_SIZE];
+        // Version of the format.
+        raw[0x0] = 0x03;
+        // Mii ID flags: a regular Mii, as opposed to a "Special" one.
+        raw[0xC] = 0x80;
+
+        Self::decode(raw)
+    }
+
+    // Source for the representation and what each thing means: https://www.3dbrew.org/wiki/Mii
+    fn decode(raw: [u8; RAW_SIZE]) -> Self {
+        let raw_options = raw[0x1];
+        let raw_position = raw[0x2];
+        let raw_device = raw[0x3];
+        let raw_details = read_u16(&raw, 0x18);
+        let raw_face_style = raw[0x30];
+        let raw_face_details = raw[0x31];
+        let raw_hair_details = raw[0x33];
+        let raw_eye_details = read_u32(&raw, 0x34);
+        let raw_eyebrow_details = read_u32(&raw, 0x38);
+        let raw_nose_details = read_u16(&raw, 0x3C);
+        let raw_mouth_details = read_u16(&raw, 0x3E);
+        let raw_mustache_details = read_u16(&raw, 0x40);
+        let raw_beard_details = read_u16(&raw, 0x42);
+        let raw_glass_details = read_u16(&raw, 0x44);
+        let raw_mole_details = read_u16(&raw, 0x46);
+
+        let mut system_id = [0; 8];
+        system_id.copy_from_slice(&raw[0x4..0xC]);
+        let mut mac_address = [0; 6];
+        mac_address.copy_from_slice(&raw[0x10..0x16]);
+
+        let options = MiiDataOptions {
+            is_copying_allowed: bits(raw_options, 0, 1) != 0,
+            is_profanity_flag_enabled: bits(raw_options, 1, 1) != 0,
+            region_lock: match bits(raw_options, 2, 2) {
+                0 => RegionLock::None,
+                1 => RegionLock::Japan,
+                2 => RegionLock::USA,
+                _ => RegionLock::Europe,
+            },
+            charset: match bits(raw_options, 4, 2) {
+                0 => Charset::JapanUSAEurope,
+                1 => Charset::China,
+                2 => Charset::Korea,
+                _ => Charset::Taiwan,
+            },
+        };
+
+        let selector_position = SelectorPosition {
+            page_index: bits(raw_position, 0, 4),
+            slot_index: bits(raw_position, 4, 4),
+        };
+
+        let console_identity = ConsoleIdentity {
+            origin_console: decode_origin_console(raw_device),
+        };
+
+        let details = Details {
+            sex: match bits(raw_details, 0, 1) {
+                0 => MiiSex::Male,
+                _ => MiiSex::Female,
+            },
+            birthday_month: bits(raw_details, 1, 4),
+            birthday_day: bits(raw_details, 5, 5),
+            shirt_color: bits(raw_details, 10, 4),
+            is_favorite: bits(raw_details, 14, 1) != 0,
+        };
+
+        let face_details = FaceDetails {
+            style: FaceStyle {
+                is_sharing_enabled: bits(raw_face_style, 0, 1) == 0,
+                shape: bits(raw_face_style, 1, 4),
+                skin_color: bits(raw_face_style, 5, 3),
+            },
+            wrinkles: bits(raw_face_details, 0, 4),
+            makeup: bits(raw_face_details, 4, 4),
+        };
+
+        let hair_details = HairDetails {
+            style: raw[0x32],
+            color: bits(raw_hair_details, 0, 3),
+            is_flipped: bits(raw_hair_details, 3, 1) != 0,
+        };
+
+        let eye_details = EyeDetails {
+            style: bits(raw_eye_details, 0, 6),
+            color: bits(raw_eye_details, 6, 3),
+            scale: bits(raw_eye_details, 9, 4),
+            y_scale: bits(raw_eye_details, 13, 3),
+            rotation: bits(raw_eye_details, 16, 5),
+            x_spacing: bits(raw_eye_details, 21, 4),
+            y_position: bits(raw_eye_details, 25, 5),
+        };
+
+        let eyebrow_details = EyebrowDetails {
+            style: bits(raw_eyebrow_details, 0, 5),
+            color: bits(raw_eyebrow_details, 5, 3),
+            scale: bits(raw_eyebrow_details, 8, 4),
+            // Bits are skipped here, following the 3dbrew wiki:
+            // https://www.3dbrew.org/wiki/Mii#Mii_format offset 0x38
+            y_scale: bits(raw_eyebrow_details, 12, 3),
+            rotation: bits(raw_eyebrow_details, 16, 4),
+            x_spacing: bits(raw_eyebrow_details, 21, 4),
+            y_position: bits(raw_eyebrow_details, 25, 5),
+        };
+
+        let nose_details = NoseDetails {
+            style: bits(raw_nose_details, 0, 5),
+            scale: bits(raw_nose_details, 5, 4),
+            y_position: bits(raw_nose_details, 9, 5),
+        };
+
+        let mouth_details = MouthDetails {
+            style: bits(raw_mouth_details, 0, 6),
+            color: bits(raw_mouth_details, 6, 3),
+            scale: bits(raw_mouth_details, 9, 4),
+            y_scale: bits(raw_mouth_details, 13, 3),
+        };
+
+        let mustache_details = MustacheDetails {
+            mouth_y_position: bits(raw_mustache_details, 0, 5),
+            mustache_style: bits(raw_mustache_details, 5, 3),
+        };
+
+        let beard_details = BeardDetails {
+            style: bits(raw_beard_details, 0, 3),
+            color: bits(raw_beard_details, 3, 3),
+            scale: bits(raw_beard_details, 6, 4),
+            y_position: bits(raw_beard_details, 10, 5),
+        };
+
+        let glass_details = GlassDetails {
+            style: bits(raw_glass_details, 0, 4),
+            color: bits(raw_glass_details, 4, 3),
+            scale: bits(raw_glass_details, 7, 4),
+            y_position: bits(raw_glass_details, 11, 5),
+        };
+
+        let mole_details = MoleDetails {
+            is_enabled: bits(raw_mole_details, 0, 1) != 0,
+            scale: bits(raw_mole_details, 1, 4),
+            x_position: bits(raw_mole_details, 5, 5),
+            y_position: bits(raw_mole_details, 10, 5),
+        };
+
+        MiiData {
+            options,
+            selector_position,
+            console_identity,
+            system_id,
+            mac_address,
+            details,
+            name: utf16_byte_pairs_to_string(&raw[NAME_RANGE]),
+            height: raw[0x2E],
+            width: raw[0x2F],
+            face_details,
+            hair_details,
+            eye_details,
+            eyebrow_details,
+            nose_details,
+            mouth_details,
+            mustache_details,
+            beard_details,
+            glass_details,
+            mole_details,
+            author_name: utf16_byte_pairs_to_string(&raw[AUTHOR_NAME_RANGE]),
+            raw,
+        }
+    }
+
+    /// Writes the fields over the raw data the Mii was decoded from, so that unknown bits are kept.
+    fn encode(&self) -> [u8; RAW_SIZE] {
+        let mut raw = self.raw;
+
+        let options = &self.options;
+        set_bits(&mut raw[0x1], 0, 1, options.is_copying_allowed.into());
+        set_bits(
+            &mut raw[0x1],
+            1,
+            1,
+            options.is_profanity_flag_enabled.into(),
+        );
+        set_bits(&mut raw[0x1], 2, 2, options.region_lock as u8);
+        set_bits(&mut raw[0x1], 4, 2, options.charset as u8);
+
+        set_bits(&mut raw[0x2], 0, 4, self.selector_position.page_index);
+        set_bits(&mut raw[0x2], 4, 4, self.selector_position.slot_index);
+
+        // Several values decode to `WiiUSwitch`, so only write the origin if it was changed.
+        let origin_console = self.console_identity.origin_console;
+        if decode_origin_console(raw[0x3]) != origin_console {
+            set_bits(&mut raw[0x3], 4, 3, origin_console as u8 + 1);
+        }
+
+        raw[0x4..0xC].copy_from_slice(&self.system_id);
+        raw[0x10..0x16].copy_from_slice(&self.mac_address);
+
+        let details = &self.details;
+        let mut raw_details = read_u16(&raw, 0x18);
+        set_bits(&mut raw_details, 0, 1, details.sex as u8);
+        set_bits(&mut raw_details, 1, 4, details.birthday_month);
+        set_bits(&mut raw_details, 5, 5, details.birthday_day);
+        set_bits(&mut raw_details, 10, 4, details.shirt_color);
+        set_bits(&mut raw_details, 14, 1, details.is_favorite.into());
+        write_u16(&mut raw, 0x18, raw_details);
+
+        encode_name(&mut raw[NAME_RANGE], &self.name);
+
+        raw[0x2E] = self.height;
+        raw[0x2F] = self.width;
+
+        let face = &self.face_details;
+        set_bits(
+            &mut raw[0x30],
+            0,
+            1,
+            (!face.style.is_sharing_enabled).into(),
+        );
+        set_bits(&mut raw[0x30], 1, 4, face.style.shape);
+        set_bits(&mut raw[0x30], 5, 3, face.style.skin_color);
+        set_bits(&mut raw[0x31], 0, 4, face.wrinkles);
+        set_bits(&mut raw[0x31], 4, 4, face.makeup);
+
+        raw[0x32] = self.hair_details.style;
+        set_bits(&mut raw[0x33], 0, 3, self.hair_details.color);
+        set_bits(&mut raw[0x33], 3, 1, self.hair_details.is_flipped.into());
+
+        let eye = &self.eye_details;
+        let mut raw_eye = read_u32(&raw, 0x34);
+        set_bits(&mut raw_eye, 0, 6, eye.style);
+        set_bits(&mut raw_eye, 6, 3, eye.color);
+        set_bits(&mut raw_eye, 9, 4, eye.scale);
+        set_bits(&mut raw_eye, 13, 3, eye.y_scale);
+        set_bits(&mut raw_eye, 16, 5, eye.rotation);
+        set_bits(&mut raw_eye, 21, 4, eye.x_spacing);
+        set_bits(&mut raw_eye, 25, 5, eye.y_position);
+        write_u32(&mut raw, 0x34, raw_eye);
+
+        let eyebrow = &self.eyebrow_details;
+        let mut raw_eyebrow = read_u32(&raw, 0x38);
+        set_bits(&mut raw_eyebrow, 0, 5, eyebrow.style);
+        set_bits(&mut raw_eyebrow, 5, 3, eyebrow.color);
+        set_bits(&mut raw_eyebrow, 8, 4, eyebrow.scale);
+        set_bits(&mut raw_eyebrow, 12, 3, eyebrow.y_scale);
+        set_bits(&mut raw_eyebrow, 16, 4, eyebrow.rotation);
+        set_bits(&mut raw_eyebrow, 21, 4, eyebrow.x_spacing);
+        set_bits(&mut raw_eyebrow, 25, 5, eyebrow.y_position);
+        write_u32(&mut raw, 0x38, raw_eyebrow);
+
+        let nose = &self.nose_details;
+        let mut raw_nose = read_u16(&raw, 0x3C);
+        set_bits(&mut raw_nose, 0, 5, nose.style);
+        set_bits(&mut raw_nose, 5, 4, nose.scale);
+        set_bits(&mut raw_nose, 9, 5, nose.y_position);
+        write_u16(&mut raw, 0x3C, raw_nose);
+
+        let mouth = &self.mouth_details;
+        let mut raw_mouth = read_u16(&raw, 0x3E);
+        set_bits(&mut raw_mouth, 0, 6, mouth.style);
+        set_bits(&mut raw_mouth, 6, 3, mouth.color);
+        set_bits(&mut raw_mouth, 9, 4, mouth.scale);
+        set_bits(&mut raw_mouth, 13, 3, mouth.y_scale);
+        write_u16(&mut raw, 0x3E, raw_mouth);
+
+        let mustache = &self.mustache_details;
+        let mut raw_mustache = read_u16(&raw, 0x40);
+        set_bits(&mut raw_mustache, 0, 5, mustache.mouth_y_position);
+        set_bits(&mut raw_mustache, 5, 3, mustache.mustache_style);
+        write_u16(&mut raw, 0x40, raw_mustache);
+
+        let beard = &self.beard_details;
+        let mut raw_beard = read_u16(&raw, 0x42);
+        set_bits(&mut raw_beard, 0, 3, beard.style);
+        set_bits(&mut raw_beard, 3, 3, beard.color);
+        set_bits(&mut raw_beard, 6, 4, beard.scale);
+        set_bits(&mut raw_beard, 10, 5, beard.y_position);
+        write_u16(&mut raw, 0x42, raw_beard);
+
+        let glass = &self.glass_details;
+        let mut raw_glass = read_u16(&raw, 0x44);
+        set_bits(&mut raw_glass, 0, 4, glass.style);
+        set_bits(&mut raw_glass, 4, 3, glass.color);
+        set_bits(&mut raw_glass, 7, 4, glass.scale);
+        set_bits(&mut raw_glass, 11, 5, glass.y_position);
+        write_u16(&mut raw, 0x44, raw_glass);
+
+        let mole = &self.mole_details;
+        let mut raw_mole = read_u16(&raw, 0x46);
+        set_bits(&mut raw_mole, 0, 1, mole.is_enabled.into());
+        set_bits(&mut raw_mole, 1, 4, mole.scale);
+        set_bits(&mut raw_mole, 5, 5, mole.x_position);
+        set_bits(&mut raw_mole, 10, 5, mole.y_position);
+        write_u16(&mut raw, 0x46, raw_mole);
+
+        encode_name(&mut raw[AUTHOR_NAME_RANGE], &self.author_name);
+
+        raw
+    }
+}
+
+impl From<ctru_sys::MiiData> for MiiData {
+    fn from(mii_data: ctru_sys::MiiData) -> Self {
+        let mut raw = [0; RAW_SIZE];
+        raw[..MiiData::SIZE].copy_from_slice(&mii_data._bindgen_opaque_blob);
+
+        MiiData::decode(raw)
+    }
+}
+
+impl From<&MiiData> for ctru_sys::MiiData {
+    fn from(mii_data: &MiiData) -> Self {
+        ctru_sys::MiiData {
+            _bindgen_opaque_blob: mii_data.to_bytes(),
+        }
+    }
+}
+
+/// Size of the data covered by the checksum: the Mii data and two bytes of padding.
+const RAW_SIZE: usize = 0x5E;
+const MII_ID_RANGE: std::ops::Range<usize> = 0xC..0x10;
+const NAME_RANGE: std::ops::Range<usize> = 0x1A..0x2E;
+const AUTHOR_NAME_RANGE: std::ops::Range<usize> = 0x48..0x5C;
+
+fn decode_origin_console(raw_device: u8) -> OriginConsole {
+    match bits(raw_device, 4, 3) {
+        1 => OriginConsole::Wii,
+        2 => OriginConsole::DSi,
+        3 => OriginConsole::N3DS,
+        _ => OriginConsole::WiiUSwitch,
+    }
+}
+
+// Methods to handle "_bits_" of the little-endian bitfields of the Mii data
+
+/// Gets `len` bits of `value`, starting from the bit `offset`
+fn bits<T: Into<u32>>(value: T, offset: u32, len: u32) -> u8 {
+    ((value.into() >> offset) & ((1 << len) - 1)) as u8
+}
+
+/// Sets `len` bits of `value`, starting from the bit `offset`, to the lowest bits of `bits`
+fn set_bits<T>(value: &mut T, offset: u32, len: u32, bits: u8)
+where
+    T: Copy + Into<u32> + TryFrom<u32>,
+{
+    let mask = ((1 << len) - 1) << offset;
+    let new_value = ((*value).into() & !mask) | ((u32::from(bits) << offset) & mask);
+
+    if let Ok(new_value) = T::try_from(new_value) {
+        *value = new_value;
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn write_u16(data: &mut [u8], offset: usize, value: u16) {
+    data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// UTF-16 Strings are give in pairs of bytes (u8), this converts them into an _actual_ string
+fn utf16_byte_pairs_to_string(data: &[u8]) -> String {
+    let raw_utf16_composed = data
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .take_while(|&c| c != 0)
+        .collect::<Vec<u16>>();
+
+    String::from_utf16_lossy(raw_utf16_composed.as_slice())
+}
+
+/// Writes `name` as UTF-16 to `data`, unless it already holds that name (possibly followed by leftover bytes)
+fn encode_name(data: &mut [u8], name: &str) {
+    if utf16_byte_pairs_to_string(data) == name {
+        return;
+    }
+
+    data.fill(0);
+    for (chunk, c) in data.chunks_exact_mut(2).zip(name.encode_utf16()) {
+        chunk.copy_from_slice(&c.to_le_bytes());
+    }
+}
+
+/// CRC-16/CCITT (XMODEM variant), used to check the integrity of Mii data
+fn crc16_ccitt(data: &[u8]) -> u16 {
+    data.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ (u16::from(byte) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Mii dump in the 0x60 bytes format, assembled following the layout documented on 3dbrew.
+    pub(super) const FERRIS: [u8; MiiData::SIZE_WITH_CHECKSUM] = [
+        0x03, 0x09, 0x31, 0x30, 0x8A, 0x3B, 0x91, 0x2D, 0x04, 0xE7, 0x55, 0x10, //
+        0x9A, 0x4B, 0x2C, 0x11, 0x40, 0xF4, 0x07, 0x1C, 0x2E, 0x93, 0x00, 0x00, //
+        0x0F, 0x57, 0x46, 0x00, 0x65, 0x00, 0x72, 0x00, 0x72, 0x00, 0x69, 0x00, //
+        0x73, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x50, //
+        0x4A, 0x31, 0x21, 0x0C, 0x88, 0x68, 0x4F, 0x18, 0x23, 0x34, 0x46, 0x14, //
+        0x81, 0x10, 0x45, 0x68, 0x4D, 0x00, 0x19, 0x29, 0x12, 0x52, 0x49, 0x50, //
+        0x43, 0x00, 0x72, 0x00, 0x61, 0x00, 0x62, 0x00, 0x00, 0x00, 0x00, 0x00, //
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xEF, 0x1C, //
+    ];
+
+    #[test]
+    fn crc16_check_value() {
+        assert_eq!(crc16_ccitt(b"123456789"), 0x31C3);
+    }
+
+    #[test]
+    fn decode_dump() {
+        let mii = MiiData::from_bytes(&FERRIS).unwrap();
+
+        assert_eq!(mii.name, "Ferris");
+        assert_eq!(mii.author_name, "Crab");
+        assert!(mii.options.is_copying_allowed);
+        assert_eq!(mii.options.region_lock, RegionLock::USA);
+        assert_eq!(mii.selector_position.page_index, 1);
+        assert_eq!(mii.selector_position.slot_index, 3);
+        assert_eq!(mii.console_identity.origin_console, OriginConsole::N3DS);
+        assert_eq!(mii.mac_address, [0x40, 0xF4, 0x07, 0x1C, 0x2E, 0x93]);
+        assert_eq!(mii.details.sex, MiiSex::Female);
+        assert_eq!(mii.details.birthday_month, 7);
+        assert_eq!(mii.details.birthday_day, 24);
+        assert_eq!(mii.details.shirt_color, 5);
+        assert!(mii.details.is_favorite);
+        assert_eq!((mii.height, mii.width), (0x40, 0x50));
+        assert!(mii.face_details.style.is_sharing_enabled);
+        assert_eq!(mii.face_details.style.shape, 5);
+        assert_eq!(mii.face_details.makeup, 3);
+        assert_eq!(mii.hair_details.style, 0x21);
+        assert!(mii.hair_details.is_flipped);
+        assert_eq!(mii.eye_details.rotation, 15);
+        assert_eq!(mii.eye_details.y_position, 12);
+        assert_eq!(mii.eyebrow_details.rotation, 6);
+        assert_eq!(mii.nose_details.y_position, 8);
+        assert_eq!(mii.mouth_details.y_scale, 3);
+        assert_eq!(mii.mustache_details.mouth_y_position, 13);
+        assert_eq!(mii.beard_details.y_position, 10);
+        assert_eq!(mii.glass_details.scale, 4);
+        assert!(mii.mole_details.is_enabled);
+        assert_eq!(mii.mole_details.y_position, 20);
+    }
+
+    #[test]
+    fn decode_follows_3dbrew_bit_order() {
+        // Each field is placed at its bit offset from the 3dbrew layout, lowest bit first.
+        let mut data = [0; MiiData::SIZE];
+        data[0x1] = 1 | (3 << 2) | (2 << 4);
+        data[0x2] = 2 | (9 << 4);
+        data[0x3] = 2 << 4;
+        data[0x18..0x1A].copy_from_slice(&(11u16 << 1 | 30 << 5 | 1 << 14).to_le_bytes());
+        data[0x30] = 1 | (7 << 1) | (4 << 5);
+        data[0x31] = 6 | (1 << 4);
+        data[0x33] = 5 | (1 << 3);
+        data[0x46..0x48].copy_from_slice(&(1u16 | 3 << 1 | 17 << 5 | 9 << 10).to_le_bytes());
+
+        let mii = MiiData::from_bytes(&data).unwrap();
+
+        assert!(mii.options.is_copying_allowed);
+        assert!(!mii.options.is_profanity_flag_enabled);
+        assert_eq!(mii.options.region_lock, RegionLock::Europe);
+        assert_eq!(mii.options.charset, Charset::Korea);
+        assert_eq!(mii.selector_position.page_index, 2);
+        assert_eq!(mii.selector_position.slot_index, 9);
+        assert_eq!(mii.console_identity.origin_console, OriginConsole::DSi);
+        assert_eq!(mii.details.sex, MiiSex::Male);
+        assert_eq!(mii.details.birthday_month, 11);
+        assert_eq!(mii.details.birthday_day, 30);
+        assert_eq!(mii.details.shirt_color, 0);
+        assert!(mii.details.is_favorite);
+        assert!(!mii.face_details.style.is_sharing_enabled);
+        assert_eq!(mii.face_details.style.shape, 7);
+        assert_eq!(mii.face_details.style.skin_color, 4);
+        assert_eq!(mii.face_details.wrinkles, 6);
+        assert_eq!(mii.face_details.makeup, 1);
+        assert_eq!(mii.hair_details.color, 5);
+        assert!(mii.hair_details.is_flipped);
+        assert!(mii.mole_details.is_enabled);
+        assert_eq!(mii.mole_details.scale, 3);
+        assert_eq!(mii.mole_details.x_position, 17);
+        assert_eq!(mii.mole_details.y_position, 9);
+    }
+
+    #[test]
+    fn round_trip_is_lossless() {
+        let mii = MiiData::from_bytes(&FERRIS).unwrap();
+        assert_eq!(mii.to_bytes_with_checksum(), FERRIS);
+        assert_eq!(mii.to_bytes()[..], FERRIS[..MiiData::SIZE]);
+
+        // Unknown bits and leftovers after the names must survive too.
+        let mut data = [0; MiiData::SIZE];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i as u8).wrapping_mul(37) ^ 0xA5;
+        }
+        data[0x22] = 0;
+        data[0x23] = 0;
+        let mii = MiiData::from_bytes(&data).unwrap();
+        assert_eq!(mii.to_bytes(), data);
+    }
+
+    #[test]
+    fn edit_and_reencode() {
+        let mut mii = MiiData::from_bytes(&FERRIS).unwrap();
+        mii.name = String::from("Corro");
+        mii.details.birthday_day = 3;
+        mii.console_identity.origin_console = OriginConsole::Wii;
+
+        let edited = MiiData::from_bytes(&mii.to_bytes_with_checksum()).unwrap();
+        assert_eq!(edited.name, "Corro");
+        assert_eq!(edited.details.birthday_day, 3);
+        assert_eq!(edited.details.birthday_month, 7);
+        assert_eq!(edited.console_identity.origin_console, OriginConsole::Wii);
+        assert_eq!(edited.author_name, "Crab");
+    }
+
+    #[test]
+    fn invalid_data() {
+        let mut corrupted = FERRIS;
+        corrupted[0x1A] ^= 1;
+        assert_eq!(
+            MiiData::from_bytes(&corrupted).unwrap_err(),
+            MiiDataError::InvalidChecksum
+        );
+        assert_eq!(
+            MiiData::from_bytes(&FERRIS[..0x40]).unwrap_err(),
+            MiiDataError::InvalidSize(0x40)
+        );
+    }
+}
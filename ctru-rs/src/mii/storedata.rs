@@ -0,0 +1,268 @@
+//! Switch packed Mii format
+//!
+//! The Switch stores its Miis as `nn::mii::StoreData`: the features of a [`CharInfo`] packed as bitfields
+//! (the "core data"), followed by the create ID and two checksums.
+//!
+//! | Offset | Size | Contents                                                           |
+//! |--------|------|--------------------------------------------------------------------|
+//! | 0x00   | 0x1C | Features, as bitfields starting from the lowest bit of each byte   |
+//! | 0x1C   | 0x14 | Nickname, 10 UTF-16 characters with no terminator                  |
+//! | 0x30   | 0x10 | Create ID                                                          |
+//! | 0x40   | 0x2  | Big endian CRC-16 of the first 0x40 bytes                          |
+//! | 0x42   | 0x2  | Big endian CRC-16 of the device ID, then of the first 0x42 bytes   |
+//!
+//! The second checksum ties the Mii to the console that made it, through a 16 bytes device ID (the
+//! author ID of the console). The 3DS has no way to read the ID of a Switch, so it is given by the caller:
+//! [`from_char_info`] computes it for the given ID, and [`is_from_device`] checks it.
+//!
+//! The features mostly hold the raw [`CharInfo`] values, except the eyebrow height which is stored minus 3.
+
+use super::charinfo::CharInfo;
+use super::{bits, crc16_ccitt, encode_name, set_bits, utf16_byte_pairs_to_string, MiiDataError};
+
+/// Size of the raw StoreData.
+pub const SIZE: usize = 0x44;
+
+/// Packs a Mii to the raw StoreData, made on the console with the given `device_id`.
+///
+/// Values too large for their field are truncated, and only the first 10 characters of the nickname are kept.
+pub fn from_char_info(info: &CharInfo, device_id: &[u8; 16]) -> [u8; SIZE] {
+    let mut data = [0; SIZE];
+
+    let mut info = info.clone();
+    info.eyebrow_y = info.eyebrow_y.saturating_sub(EYEBROW_Y_OFFSET);
+    for (field, byte, offset, len) in fields_mut(&mut info) {
+        set_bits(&mut data[byte], offset, len, *field);
+    }
+
+    encode_name(&mut data[NICKNAME_OFFSET..CREATE_ID_OFFSET], &info.nickname);
+    data[CREATE_ID_OFFSET..DATA_CRC_OFFSET].copy_from_slice(&info.create_id);
+
+    let data_crc = crc16_ccitt(&data[..DATA_CRC_OFFSET]);
+    data[DATA_CRC_OFFSET..DEVICE_CRC_OFFSET].copy_from_slice(&data_crc.to_be_bytes());
+    let device_crc = device_crc(&data, device_id);
+    data[DEVICE_CRC_OFFSET..].copy_from_slice(&device_crc.to_be_bytes());
+
+    data
+}
+
+/// Unpacks raw StoreData to a Mii.
+///
+/// Only the checksum of the data is checked, as the device one depends on the console that made the Mii,
+/// see [`is_from_device`].
+pub fn to_char_info(data: &[u8]) -> Result<CharInfo, MiiDataError> {
+    if data.len() != SIZE {
+        return Err(MiiDataError::InvalidSize(data.len()));
+    }
+
+    let checksum = u16::from_be_bytes([data[DATA_CRC_OFFSET], data[DATA_CRC_OFFSET + 1]]);
+    if crc16_ccitt(&data[..DATA_CRC_OFFSET]) != checksum {
+        return Err(MiiDataError::InvalidChecksum);
+    }
+
+    let mut info = CharInfo {
+        nickname: utf16_byte_pairs_to_string(&data[NICKNAME_OFFSET..CREATE_ID_OFFSET]),
+        ..Default::default()
+    };
+    info.create_id
+        .copy_from_slice(&data[CREATE_ID_OFFSET..DATA_CRC_OFFSET]);
+
+    for (field, byte, offset, len) in fields_mut(&mut info) {
+        *field = bits(data[byte], offset, len);
+    }
+    info.eyebrow_y += EYEBROW_Y_OFFSET;
+
+    Ok(info)
+}
+
+/// Returns `true` if the device checksum of the raw StoreData matches the given `device_id`.
+pub fn is_from_device(data: &[u8; SIZE], device_id: &[u8; 16]) -> bool {
+    let checksum = u16::from_be_bytes([data[DEVICE_CRC_OFFSET], data[DEVICE_CRC_OFFSET + 1]]);
+    device_crc(data, device_id) == checksum
+}
+
+const NICKNAME_OFFSET: usize = 0x1C;
+const CREATE_ID_OFFSET: usize = 0x30;
+const DATA_CRC_OFFSET: usize = 0x40;
+const DEVICE_CRC_OFFSET: usize = 0x42;
+const FIELD_COUNT: usize = 49;
+/// The eyebrow height ranges from 3 to 18, and is stored minus 3 to fit in 4 bits.
+const EYEBROW_Y_OFFSET: u8 = 3;
+
+fn device_crc(data: &[u8; SIZE], device_id: &[u8; 16]) -> u16 {
+    let mut input = [0; 16 + DEVICE_CRC_OFFSET];
+    input[..16].copy_from_slice(device_id);
+    input[16..].copy_from_slice(&data[..DEVICE_CRC_OFFSET]);
+    crc16_ccitt(&input)
+}
+
+/// Returns the features with their byte, and the offset and size of their bits in that byte.
+fn fields_mut(info: &mut CharInfo) -> [(&mut u8, usize, u32, u32); FIELD_COUNT] {
+    [
+        (&mut info.hair_type, 0x00, 0, 8),
+        (&mut info.height, 0x01, 0, 7),
+        (&mut info.mole_type, 0x01, 7, 1),
+        (&mut info.build, 0x02, 0, 7),
+        (&mut info.hair_flip, 0x02, 7, 1),
+        (&mut info.hair_color, 0x03, 0, 7),
+        (&mut info.mii_type, 0x03, 7, 1),
+        (&mut info.eye_color, 0x04, 0, 7),
+        (&mut info.gender, 0x04, 7, 1),
+        (&mut info.eyebrow_color, 0x05, 0, 7),
+        (&mut info.mouth_color, 0x06, 0, 7),
+        (&mut info.beard_color, 0x07, 0, 7),
+        (&mut info.glass_color, 0x08, 0, 7),
+        (&mut info.eye_type, 0x09, 0, 6),
+        (&mut info.region_move, 0x09, 6, 2),
+        (&mut info.mouth_type, 0x0A, 0, 6),
+        (&mut info.font_region, 0x0A, 6, 2),
+        (&mut info.eye_y, 0x0B, 0, 5),
+        (&mut info.glass_scale, 0x0B, 5, 3),
+        (&mut info.eyebrow_type, 0x0C, 0, 5),
+        (&mut info.mustache_type, 0x0C, 5, 3),
+        (&mut info.nose_type, 0x0D, 0, 5),
+        (&mut info.beard_type, 0x0D, 5, 3),
+        (&mut info.nose_y, 0x0E, 0, 5),
+        (&mut info.mouth_aspect, 0x0E, 5, 3),
+        (&mut info.mouth_y, 0x0F, 0, 5),
+        (&mut info.eyebrow_aspect, 0x0F, 5, 3),
+        (&mut info.mustache_y, 0x10, 0, 5),
+        (&mut info.eye_rotate, 0x10, 5, 3),
+        (&mut info.glass_y, 0x11, 0, 5),
+        (&mut info.eye_aspect, 0x11, 5, 3),
+        (&mut info.mole_x, 0x12, 0, 5),
+        (&mut info.eye_scale, 0x12, 5, 3),
+        (&mut info.mole_y, 0x13, 0, 5),
+        (&mut info.glass_type, 0x14, 0, 5),
+        (&mut info.favorite_color, 0x15, 0, 4),
+        (&mut info.faceline_type, 0x15, 4, 4),
+        (&mut info.faceline_color, 0x16, 0, 4),
+        (&mut info.faceline_wrinkle, 0x16, 4, 4),
+        (&mut info.faceline_make, 0x17, 0, 4),
+        (&mut info.eye_x, 0x17, 4, 4),
+        (&mut info.eyebrow_scale, 0x18, 0, 4),
+        (&mut info.eyebrow_rotate, 0x18, 4, 4),
+        (&mut info.eyebrow_x, 0x19, 0, 4),
+        (&mut info.eyebrow_y, 0x19, 4, 4),
+        (&mut info.nose_scale, 0x1A, 0, 4),
+        (&mut info.mouth_scale, 0x1A, 4, 4),
+        (&mut info.mustache_scale, 0x1B, 0, 4),
+        (&mut info.mole_scale, 0x1B, 4, 4),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mii::tests::FERRIS;
+    use crate::mii::MiiData;
+
+    const DEVICE_ID: [u8; 16] = *b"0123456789abcdef";
+
+    /// Core data with the values of the [`CharInfo`] in `core_data_layout`.
+    fn core_data() -> [u8; SIZE] {
+        let mut data = [0; SIZE];
+        data[..NICKNAME_OFFSET].copy_from_slice(&[
+            0x21, // hair_type 33
+            0xC0, // height 64, mole_type 1
+            0x50, // build 80, hair_flip 0
+            0x84, // hair_color 4, mii_type 1
+            0x8A, // eye_color 10, gender 1
+            0x01, 0x14, 0x03, 0x0E, // eyebrow, mouth, beard and glasses colours
+            0x48, // eye_type 8, region_move 1
+            0x45, // mouth_type 5, font_region 1
+            0x8C, // eye_y 12, glass_scale 4
+            0x43, // eyebrow_type 3, mustache_type 2
+            0x21, // nose_type 1, beard_type 1
+            0x68, // nose_y 8, mouth_aspect 3
+            0x6D, // mouth_y 13, eyebrow_aspect 3
+            0xEA, // mustache_y 10, eye_rotate 7
+            0x6A, // glass_y 10, eye_aspect 3
+            0x82, // mole_x 2, eye_scale 4
+            0x14, // mole_y 20
+            0x02, // glass_type 2
+            0x55, // favorite_color 5, faceline_type 5
+            0x12, // faceline_color 2, faceline_wrinkle 1
+            0x23, // faceline_make 3, eye_x 2
+            0x64, // eyebrow_scale 4, eyebrow_rotate 6
+            0x72, // eyebrow_x 2, eyebrow_y 10 (stored as 7)
+            0x44, // nose_scale 4, mouth_scale 4
+            0x44, // mustache_scale 4, mole_scale 4
+        ]);
+        encode_name(&mut data[NICKNAME_OFFSET..CREATE_ID_OFFSET], "Ferris");
+        data[CREATE_ID_OFFSET..DATA_CRC_OFFSET].copy_from_slice(&[0x11; 16]);
+        let crc = crc16_ccitt(&data[..DATA_CRC_OFFSET]);
+        data[DATA_CRC_OFFSET..DEVICE_CRC_OFFSET].copy_from_slice(&crc.to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn fields_do_not_overlap() {
+        let mut used = [0u8; NICKNAME_OFFSET];
+        for (_, byte, offset, len) in fields_mut(&mut CharInfo::default()) {
+            assert!(offset + len <= 8);
+            let mask = (((1u32 << len) - 1) << offset) as u8;
+            assert_eq!(used[byte] & mask, 0);
+            used[byte] |= mask;
+        }
+    }
+
+    #[test]
+    fn core_data_layout() {
+        let info = to_char_info(&core_data()).unwrap();
+
+        assert_eq!(info.nickname, "Ferris");
+        assert_eq!(info.create_id, [0x11; 16]);
+        assert_eq!((info.hair_type, info.height, info.mole_type), (33, 64, 1));
+        assert_eq!((info.build, info.hair_flip), (80, 0));
+        assert_eq!((info.hair_color, info.mii_type), (4, 1));
+        assert_eq!((info.eye_color, info.gender), (10, 1));
+        assert_eq!((info.mouth_color, info.glass_color), (20, 14));
+        assert_eq!((info.eye_type, info.region_move), (8, 1));
+        assert_eq!((info.mouth_type, info.font_region), (5, 1));
+        assert_eq!((info.mustache_y, info.eye_rotate), (10, 7));
+        assert_eq!((info.mole_y, info.glass_type), (20, 2));
+        assert_eq!((info.favorite_color, info.faceline_type), (5, 5));
+        assert_eq!((info.eyebrow_x, info.eyebrow_y), (2, 10));
+        assert_eq!((info.mustache_scale, info.mole_scale), (4, 4));
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = core_data();
+        let encoded = from_char_info(&to_char_info(&data).unwrap(), &DEVICE_ID);
+
+        assert_eq!(encoded[..DEVICE_CRC_OFFSET], data[..DEVICE_CRC_OFFSET]);
+        assert!(is_from_device(&encoded, &DEVICE_ID));
+        assert!(!is_from_device(&encoded, &[0; 16]));
+    }
+
+    #[test]
+    fn invalid_data() {
+        let info = CharInfo::from(&MiiData::from_bytes(&FERRIS).unwrap());
+        let mut data = from_char_info(&info, &DEVICE_ID);
+
+        assert_eq!(
+            to_char_info(&data[..0x40]).unwrap_err(),
+            MiiDataError::InvalidSize(0x40)
+        );
+        data[NICKNAME_OFFSET] ^= 1;
+        assert_eq!(
+            to_char_info(&data).unwrap_err(),
+            MiiDataError::InvalidChecksum
+        );
+    }
+
+    #[test]
+    fn values_are_truncated() {
+        let info = CharInfo {
+            gender: 3,
+            nickname: "A long nickname".to_owned(),
+            ..Default::default()
+        };
+
+        let decoded = to_char_info(&from_char_info(&info, &DEVICE_ID)).unwrap();
+        assert_eq!(decoded.gender, 1);
+        assert_eq!(decoded.nickname, "A long nic");
+    }
+}
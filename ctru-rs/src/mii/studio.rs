@@ -0,0 +1,158 @@
+//! Mii Studio format
+//!
+//! Mii Studio, Nintendo's web Mii editor, represents a Mii as 46 bytes, one per feature, in the alphabetical
+//! order of the [`CharInfo`] field names. This data is shared as an hex string, after an obfuscation
+//! where each byte depends on the previous one.
+//!
+//! The Studio data holds neither the name nor the IDs of a Mii: converting to this format and back only keeps
+//! its appearance.
+
+use super::charinfo::CharInfo;
+use super::MiiDataError;
+
+/// Size of the raw Mii Studio data.
+pub const SIZE: usize = 46;
+/// Length of the obfuscated hex string, which starts with the seed of the obfuscation.
+pub const HEX_LEN: usize = (SIZE + 1) * 2;
+
+/// Converts a Mii to the raw Mii Studio data.
+pub fn from_char_info(info: &CharInfo) -> [u8; SIZE] {
+    let mut info = info.clone();
+    let mut data = [0; SIZE];
+    for (byte, field) in data.iter_mut().zip(fields_mut(&mut info)) {
+        *byte = *field;
+    }
+    data
+}
+
+/// Converts raw Mii Studio data to a Mii.
+///
+/// The fields not present in the Studio data are left to their default value.
+pub fn to_char_info(data: &[u8; SIZE]) -> CharInfo {
+    let mut info = CharInfo::default();
+    for (field, &byte) in fields_mut(&mut info).into_iter().zip(data) {
+        *field = byte;
+    }
+    info
+}
+
+/// Obfuscates raw Mii Studio data to the hex string used by Mii Studio.
+pub fn encode_hex(data: &[u8; SIZE]) -> String {
+    let mut previous = SEED;
+    let mut hex = format!("{SEED:02x}");
+
+    for &byte in data {
+        let encoded = (byte ^ previous).wrapping_add(7);
+        hex.push_str(&format!("{encoded:02x}"));
+        previous = encoded;
+    }
+
+    hex
+}
+
+/// Decodes an hex string as used by Mii Studio to the raw Mii Studio data.
+pub fn decode_hex(hex: &str) -> Result<[u8; SIZE], MiiDataError> {
+    if hex.len() != HEX_LEN {
+        return Err(MiiDataError::InvalidSize(hex.len()));
+    }
+
+    let mut bytes = hex.as_bytes().chunks_exact(2).map(|pair| {
+        std::str::from_utf8(pair)
+            .ok()
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+            .ok_or(MiiDataError::InvalidEncoding)
+    });
+
+    let mut previous = bytes.next().unwrap()?;
+    let mut data = [0; SIZE];
+    for (byte, encoded) in data.iter_mut().zip(bytes) {
+        let encoded = encoded?;
+        *byte = encoded.wrapping_sub(7) ^ previous;
+        previous = encoded;
+    }
+
+    Ok(data)
+}
+
+/// Seed used when obfuscating the data. Mii Studio accepts any value.
+const SEED: u8 = 0;
+
+/// Returns the fields of the Studio data, in order.
+fn fields_mut(info: &mut CharInfo) -> [&mut u8; SIZE] {
+    [
+        &mut info.beard_color,
+        &mut info.beard_type,
+        &mut info.build,
+        &mut info.eye_aspect,
+        &mut info.eye_color,
+        &mut info.eye_rotate,
+        &mut info.eye_scale,
+        &mut info.eye_type,
+        &mut info.eye_x,
+        &mut info.eye_y,
+        &mut info.eyebrow_aspect,
+        &mut info.eyebrow_color,
+        &mut info.eyebrow_rotate,
+        &mut info.eyebrow_scale,
+        &mut info.eyebrow_type,
+        &mut info.eyebrow_x,
+        &mut info.eyebrow_y,
+        &mut info.faceline_color,
+        &mut info.faceline_make,
+        &mut info.faceline_type,
+        &mut info.faceline_wrinkle,
+        &mut info.favorite_color,
+        &mut info.gender,
+        &mut info.glass_color,
+        &mut info.glass_scale,
+        &mut info.glass_type,
+        &mut info.glass_y,
+        &mut info.hair_color,
+        &mut info.hair_flip,
+        &mut info.hair_type,
+        &mut info.height,
+        &mut info.mole_scale,
+        &mut info.mole_type,
+        &mut info.mole_x,
+        &mut info.mole_y,
+        &mut info.mouth_aspect,
+        &mut info.mouth_color,
+        &mut info.mouth_scale,
+        &mut info.mouth_type,
+        &mut info.mouth_y,
+        &mut info.mustache_scale,
+        &mut info.mustache_type,
+        &mut info.mustache_y,
+        &mut info.nose_scale,
+        &mut info.nose_type,
+        &mut info.nose_y,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mii::tests::FERRIS;
+    use crate::mii::MiiData;
+
+    #[test]
+    fn studio_round_trip() {
+        let info = CharInfo::from(&MiiData::from_bytes(&FERRIS).unwrap());
+        let data = from_char_info(&info);
+        let hex = encode_hex(&data);
+        assert_eq!(hex.len(), HEX_LEN);
+        assert_eq!(decode_hex(&hex).unwrap(), data);
+
+        let decoded = to_char_info(&data);
+        assert_eq!(decoded.hair_type, info.hair_type);
+        assert_eq!(decoded.mole_y, info.mole_y);
+
+        // Each byte is obfuscated with the previous one.
+        assert!(encode_hex(&[0; SIZE]).starts_with("00070e151c"));
+        assert_eq!(decode_hex("zz").unwrap_err(), MiiDataError::InvalidSize(2));
+        assert_eq!(
+            decode_hex(&hex.replace('0', "g")).unwrap_err(),
+            MiiDataError::InvalidEncoding
+        );
+    }
+}
@@ -9,32 +9,39 @@
 //! [package.metadata.cargo-3ds]
 //! romfs_dir = "romfs"
 //! ```
+//!
+//! While a [`RomFS`] is alive, the RomFS is mounted as the `romfs` device, like a
+//! [mounted archive](crate::services::fs::Archive::mount), and its files can be read through
+//! [`std::fs`] with paths such as `romfs:/dir/file.txt`, see [`Device`].
 
 use crate::error::ResultCode;
 use std::ffi::CStr;
 use std::sync::Mutex;
 
+use crate::services::fs::Device;
 use crate::services::ServiceReference;
 
+/// Guard keeping the RomFS mounted. The RomFS is unmounted when every instance is dropped.
 pub struct RomFS {
     _service_handler: ServiceReference,
 }
 
 static ROMFS_ACTIVE: Mutex<usize> = Mutex::new(0);
 
+/// Name of the device the RomFS is mounted as.
+const MOUNT_NAME: &[u8] = b"romfs\0";
+
 impl RomFS {
     pub fn init() -> crate::Result<Self> {
         let _service_handler = ServiceReference::new(
             &ROMFS_ACTIVE,
             true,
             || {
-                let mount_name = CStr::from_bytes_with_nul(b"romfs\0").unwrap();
-                ResultCode(unsafe { ctru_sys::romfsMountSelf(mount_name.as_ptr()) })?;
+                ResultCode(unsafe { ctru_sys::romfsMountSelf(mount_name().as_ptr()) })?;
                 Ok(())
             },
             || {
-                let mount_name = CStr::from_bytes_with_nul(b"romfs\0").unwrap();
-                unsafe { ctru_sys::romfsUnmount(mount_name.as_ptr()) };
+                let _ = unsafe { ctru_sys::romfsUnmount(mount_name().as_ptr()) };
             },
         )?;
 
@@ -42,6 +49,16 @@ impl RomFS {
     }
 }
 
+impl Device for RomFS {
+    fn name(&self) -> &str {
+        mount_name().to_str().unwrap()
+    }
+}
+
+fn mount_name() -> &'static CStr {
+    CStr::from_bytes_with_nul(MOUNT_NAME).unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,7 +1,197 @@
+//! Applet service
+//!
+//! APT manages the lifecycle of the application: it handles the HOME and POWER buttons, sleep mode,
+//! and the communication with the other applets running on the system.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
 use crate::error::ResultCode;
+use crate::services::fs::MediaType;
 
 pub struct Apt(());
 
+/// Lifecycle events notified by APT.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AptEvent {
+    /// The application is being suspended, e.g. to show the HOME menu.
+    Suspend = ctru_sys::APTHOOK_ONSUSPEND,
+    /// The application is back in the foreground after being suspended.
+    Restore = ctru_sys::APTHOOK_ONRESTORE,
+    /// The console is entering sleep mode, e.g. because the shell was closed.
+    Sleep = ctru_sys::APTHOOK_ONSLEEP,
+    /// The console woke up from sleep mode.
+    WakeUp = ctru_sys::APTHOOK_ONWAKEUP,
+    /// The application was asked to close, and [`Apt::main_loop`] is about to return `false`.
+    Exit = ctru_sys::APTHOOK_ONEXIT,
+}
+
+/// Identifier of an applet or application known to APT.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AppId {
+    None,
+    HomeMenu,
+    Camera,
+    FriendsList,
+    GameNotes,
+    Web,
+    InstructionManual,
+    Notifications,
+    Miiverse,
+    MiiversePosting,
+    AmiiboSettings,
+    Application,
+    Eshop,
+    SoftwareKeyboard,
+    AppletEd,
+    PNoteAp,
+    SNoteAp,
+    Error,
+    Mint,
+    ExtraPad,
+    Memolib,
+    /// An ID without a named variant.
+    Unknown(u32),
+}
+
+type HookCallback = Box<dyn FnMut(AptEvent) + Send>;
+type MessageCallbackFn = Box<dyn FnMut(AppId, &[u8]) + Send>;
+
+/// Maximum size of the parameter of a deliver argument.
+pub const DELIVER_ARG_PARAM_SIZE: usize = 0x300;
+
+/// Size of the HMAC of a deliver argument.
+pub const DELIVER_ARG_HMAC_SIZE: usize = 0x20;
+
+/// Identifier of a title, such as an application or a system applet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TitleId(pub u64);
+
+/// Argument passed to an application when it's launched by another one.
+///
+/// See [`Apt::jump_to_application`] and [`Apt::receive_deliver_arg`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeliverArg {
+    sender: TitleId,
+    param: Vec<u8>,
+    hmac: [u8; DELIVER_ARG_HMAC_SIZE],
+}
+
+/// Maximum size of the payload of a parameter.
+pub const PARAMETER_MAX_SIZE: usize = 0x1000;
+
+/// Command carried by a parameter, telling the receiver what to do with it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AptCommand {
+    None,
+    /// The receiver should wake up. Used to start library applets.
+    WakeUp,
+    /// The sender makes a request to the receiver.
+    Request,
+    /// The receiver replies to a request.
+    Response,
+    Exit,
+    Message,
+    HomeButtonOnce,
+    HomeButtonTwice,
+    DspSleep,
+    DspWakeUp,
+    /// The receiver wakes up because the applet it launched exited.
+    WakeUpExit,
+    WakeUpPause,
+    WakeUpCancel,
+    WakeUpCancelAll,
+    WakeUpPowerButton,
+    WakeUpJumpToHome,
+    SysAppletRequest,
+    WakeUpLaunchApp,
+    /// A command without a named variant.
+    Unknown(u32),
+}
+
+/// A message exchanged between applets, see [`Apt::send_parameter`] and [`Apt::receive_parameter`].
+#[derive(Debug)]
+pub struct Parameter {
+    sender: AppId,
+    command: AptCommand,
+    payload: Vec<u8>,
+    handle: Option<ParameterHandle>,
+}
+
+/// A kernel handle sent along with a [`Parameter`], usually to share memory with the receiver.
+///
+/// The handle is closed when this struct is dropped.
+#[derive(Debug)]
+pub struct ParameterHandle(ctru_sys::Handle);
+
+/// Reply to a sleep query, see [`Apt::reply_sleep_query`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum SleepQueryReply {
+    /// Refuse to enter sleep mode.
+    Reject = ctru_sys::APTREPLY_REJECT,
+    /// Allow the console to enter sleep mode.
+    Accept = ctru_sys::APTREPLY_ACCEPT,
+    /// Postpone the answer.
+    Later = ctru_sys::APTREPLY_LATER,
+}
+
+/// A lifecycle hook registered with [`Apt::hook`].
+///
+/// The hook is removed when this struct is dropped.
+pub struct AptHook<'apt> {
+    cookie: Box<ctru_sys::aptHookCookie>,
+    _callback: Box<HookCallback>,
+    _apt: PhantomData<&'apt Apt>,
+}
+
+/// A queue of lifecycle events, created by [`Apt::event_queue`].
+///
+/// Events are recorded while [`Apt::main_loop`] runs, so they can only be read after the fact:
+/// for example, a [`AptEvent::Sleep`] event is read once the console has already woken up.
+/// Use [`Apt::hook`] to act right when the event happens.
+pub struct AptEventQueue<'apt> {
+    events: Arc<Mutex<VecDeque<AptEvent>>>,
+    _hook: AptHook<'apt>,
+}
+
+/// A message callback registered with [`Apt::set_message_callback`].
+///
+/// The callback is unregistered when this struct is dropped.
+pub struct MessageCallback<'apt> {
+    callback: Box<MessageCallbackFn>,
+    _apt: PhantomData<&'apt Apt>,
+}
+
+/// Scoped HOME button policy, created by [`Apt::scoped_home_allowed`].
+///
+/// The previous policy is restored when this struct is dropped.
+#[must_use = "the previous policy is restored as soon as the guard is dropped"]
+pub struct HomePolicyGuard<'apt> {
+    previous: bool,
+    _apt: PhantomData<&'apt Apt>,
+}
+
+/// Scoped sleep mode policy, created by [`Apt::scoped_sleep_allowed`].
+///
+/// The previous policy is restored when this struct is dropped.
+#[must_use = "the previous policy is restored as soon as the guard is dropped"]
+pub struct SleepPolicyGuard<'apt> {
+    previous: bool,
+    _apt: PhantomData<&'apt Apt>,
+}
+
+/// User data of the message callback currently registered in libctru.
+static CURRENT_MESSAGE_CALLBACK: AtomicPtr<MessageCallbackFn> = AtomicPtr::new(ptr::null_mut());
+
 impl Apt {
     pub fn init() -> crate::Result<Apt> {
         unsafe {
@@ -20,6 +210,462 @@ impl Apt {
             Ok(())
         }
     }
+
+    /// Launches the given title when the application exits.
+    ///
+    /// The jump happens when the [`Apt`] handle is dropped, after the application is done cleaning up.
+    /// Only titles installed on the system can be launched this way: 3DSX files have to be started by
+    /// the homebrew launcher.
+    pub fn set_chainloader(&self, title: TitleId, media: MediaType) {
+        let media: ctru_sys::FS_MediaType = media.into();
+        unsafe { ctru_sys::aptSetChainloader(title.0, media as u8) }
+    }
+
+    /// Relaunches the application when it exits, like a soft reset.
+    pub fn set_chainloader_to_self(&self) {
+        unsafe { ctru_sys::aptSetChainloaderToSelf() }
+    }
+
+    /// Cancels a previous call to [`Apt::set_chainloader`] or [`Apt::set_chainloader_to_self`].
+    pub fn clear_chainloader(&self) {
+        unsafe { ctru_sys::aptClearChainloader() }
+    }
+
+    /// Closes the application and launches the given title right away.
+    ///
+    /// `param` and `hmac` are delivered to the launched title, which can read them with
+    /// [`Apt::receive_deliver_arg`]. Most titles don't check the HMAC, so it can be left zeroed.
+    ///
+    /// On success, [`Apt::main_loop`] returns `false` from now on, and the application should exit
+    /// as soon as possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `param` is longer than [`DELIVER_ARG_PARAM_SIZE`].
+    pub fn jump_to_application(
+        &self,
+        title: TitleId,
+        media: MediaType,
+        param: &[u8],
+        hmac: &[u8; DELIVER_ARG_HMAC_SIZE],
+    ) -> crate::Result<()> {
+        assert!(
+            param.len() <= DELIVER_ARG_PARAM_SIZE,
+            "deliver argument is too long ({} bytes, the maximum is {DELIVER_ARG_PARAM_SIZE})",
+            param.len()
+        );
+
+        let media: ctru_sys::FS_MediaType = media.into();
+
+        unsafe {
+            ResultCode(ctru_sys::APT_PrepareToDoApplicationJump(
+                0,
+                title.0,
+                media as u8,
+            ))?;
+            ResultCode(ctru_sys::APT_DoApplicationJump(
+                param.as_ptr() as *const _,
+                param.len(),
+                hmac.as_ptr() as *const _,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the argument the application was launched with, if it was launched by another title.
+    ///
+    /// Applications started from the homebrew launcher get their arguments through [`std::env::args`] instead.
+    pub fn receive_deliver_arg(&self) -> crate::Result<Option<DeliverArg>> {
+        let mut param = vec![0; DELIVER_ARG_PARAM_SIZE];
+        let mut hmac = [0; DELIVER_ARG_HMAC_SIZE];
+        let mut sender = 0;
+        let mut received = false;
+
+        unsafe {
+            ResultCode(ctru_sys::APT_ReceiveDeliverArg(
+                param.as_mut_ptr() as *const _,
+                param.len(),
+                hmac.as_mut_ptr() as *const _,
+                &mut sender,
+                &mut received,
+            ))?;
+        }
+
+        if !received {
+            return Ok(None);
+        }
+
+        Ok(Some(DeliverArg {
+            sender: TitleId(sender),
+            param,
+            hmac,
+        }))
+    }
+
+    /// Returns the current CPU time limit of the application, as a percentage.
+    pub fn app_cpu_time_limit(&self) -> crate::Result<u32> {
+        let mut percent = 0;
+        unsafe {
+            ResultCode(ctru_sys::APT_GetAppCpuTimeLimit(&mut percent))?;
+        }
+        Ok(percent)
+    }
+
+    /// Returns `true` if the application runs on a New 3DS or New 2DS.
+    pub fn is_new_3ds(&self) -> crate::Result<bool> {
+        let mut is_new = false;
+        unsafe {
+            ResultCode(ctru_sys::APT_CheckNew3DS(&mut is_new))?;
+        }
+        Ok(is_new)
+    }
+
+    /// Returns `true` if the user can press the HOME button to go back to the HOME menu.
+    pub fn is_home_allowed(&self) -> bool {
+        unsafe { ctru_sys::aptIsHomeAllowed() }
+    }
+
+    /// Configures whether the user can press the HOME button to go back to the HOME menu.
+    ///
+    /// While HOME is not allowed, pressing it shows a notice on the screen instead.
+    pub fn set_home_allowed(&self, allowed: bool) {
+        unsafe { ctru_sys::aptSetHomeAllowed(allowed) }
+    }
+
+    /// Sets the HOME button policy until the returned guard is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ctru::services::apt::Apt;
+    /// # let apt = Apt::init().unwrap();
+    /// {
+    ///     let _guard = apt.scoped_home_allowed(false);
+    ///     // Write the save file without the user leaving halfway through.
+    /// }
+    /// // HOME is allowed again (if it was before).
+    /// ```
+    pub fn scoped_home_allowed(&self, allowed: bool) -> HomePolicyGuard<'_> {
+        let previous = self.is_home_allowed();
+        self.set_home_allowed(allowed);
+
+        HomePolicyGuard {
+            previous,
+            _apt: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the console can enter sleep mode while the application is running.
+    pub fn is_sleep_allowed(&self) -> bool {
+        unsafe { ctru_sys::aptIsSleepAllowed() }
+    }
+
+    /// Configures whether the console can enter sleep mode while the application is running.
+    ///
+    /// When sleep is not allowed, the application keeps running with the shell closed (with the screens off),
+    /// e.g. to keep playing music.
+    pub fn set_sleep_allowed(&self, allowed: bool) {
+        unsafe { ctru_sys::aptSetSleepAllowed(allowed) }
+    }
+
+    /// Sets the sleep mode policy until the returned guard is dropped.
+    pub fn scoped_sleep_allowed(&self, allowed: bool) -> SleepPolicyGuard<'_> {
+        let previous = self.is_sleep_allowed();
+        self.set_sleep_allowed(allowed);
+
+        SleepPolicyGuard {
+            previous,
+            _apt: PhantomData,
+        }
+    }
+
+    /// Puts the console to sleep if the shell is closed.
+    ///
+    /// This is useful after having kept the application running with [`Apt::set_sleep_allowed`],
+    /// once it's done with whatever it was doing.
+    pub fn sleep_if_shell_closed(&self) -> crate::Result<()> {
+        unsafe {
+            ResultCode(ctru_sys::APT_SleepIfShellClosed())?;
+            Ok(())
+        }
+    }
+
+    /// Replies to a sleep query sent to the application identified by `app`.
+    pub fn reply_sleep_query(&self, app: AppId, reply: SleepQueryReply) -> crate::Result<()> {
+        unsafe {
+            ResultCode(ctru_sys::APT_ReplySleepQuery(app.into(), reply as u32))?;
+            Ok(())
+        }
+    }
+
+    /// Sends a parameter from the applet `source` to the applet `dest`.
+    ///
+    /// The receiver gets its own copy of `handle`, which stays open on this side.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `payload` is longer than [`PARAMETER_MAX_SIZE`].
+    pub fn send_parameter(
+        &self,
+        source: AppId,
+        dest: AppId,
+        command: AptCommand,
+        payload: &[u8],
+        handle: Option<&ParameterHandle>,
+    ) -> crate::Result<()> {
+        assert!(
+            payload.len() <= PARAMETER_MAX_SIZE,
+            "parameter payload is too long ({} bytes, the maximum is {PARAMETER_MAX_SIZE})",
+            payload.len()
+        );
+
+        unsafe {
+            ResultCode(ctru_sys::APT_SendParameter(
+                source.into(),
+                dest.into(),
+                command.into(),
+                payload.as_ptr() as *const _,
+                payload.len() as u32,
+                handle.map_or(0, ParameterHandle::as_raw),
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Receives the parameter sent to the applet `app`, if there is one.
+    ///
+    /// This doesn't wait: `None` is returned if no parameter is pending.
+    ///
+    /// # Notes
+    ///
+    /// libctru receives the parameters sent to the application itself while handling its lifecycle
+    /// in [`Apt::main_loop`], so this is mostly useful to talk with applets through their own [`AppId`].
+    pub fn receive_parameter(&self, app: AppId) -> crate::Result<Option<Parameter>> {
+        read_parameter(ctru_sys::APT_ReceiveParameter, app)
+    }
+
+    /// Receives the parameter sent to the applet `app`, waiting up to `timeout` for one to arrive.
+    ///
+    /// `None` is returned if no parameter arrived in time.
+    pub fn receive_parameter_timeout(
+        &self,
+        app: AppId,
+        timeout: Duration,
+    ) -> crate::Result<Option<Parameter>> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(parameter) = self.receive_parameter(app)? {
+                return Ok(Some(parameter));
+            }
+
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+
+            thread::sleep(PARAMETER_POLL_INTERVAL);
+        }
+    }
+
+    /// Reads the parameter sent to the applet `app`, if there is one, without removing it from the queue.
+    pub fn glance_parameter(&self, app: AppId) -> crate::Result<Option<Parameter>> {
+        read_parameter(ctru_sys::APT_GlanceParameter, app)
+    }
+
+    /// Cancels the pending parameter sent by `source` to `dest`.
+    ///
+    /// [`AppId::None`] matches any applet. Returns `true` if a parameter was cancelled.
+    pub fn cancel_parameter(&self, source: AppId, dest: AppId) -> crate::Result<bool> {
+        let mut success = false;
+        unsafe {
+            ResultCode(ctru_sys::APT_CancelParameter(
+                source.into(),
+                dest.into(),
+                &mut success,
+            ))?;
+        }
+        Ok(success)
+    }
+
+    /// Registers a closure to be called on the lifecycle events of the application.
+    ///
+    /// The closure is called from within [`Apt::main_loop`], right before the application is suspended or put
+    /// to sleep and right after it resumes. This is the place to pause audio, save the game or stop the cameras.
+    /// [`AptEvent::Exit`] is received when `main_loop` is about to return `false` because the system asked the
+    /// application to close.
+    ///
+    /// The hook stays registered until the returned [`AptHook`] is dropped. Since it borrows the [`Apt`], it is
+    /// always unregistered before the service exits, and never sees the events sent while the service shuts down.
+    ///
+    /// # Notes
+    ///
+    /// A panic inside the closure aborts the program, since it can't unwind through libctru.
+    pub fn hook<F>(&self, callback: F) -> AptHook<'_>
+    where
+        F: FnMut(AptEvent) + Send + 'static,
+    {
+        let mut callback: Box<HookCallback> = Box::new(Box::new(callback));
+        let mut cookie = Box::<ctru_sys::aptHookCookie>::default();
+
+        unsafe {
+            ctru_sys::aptHook(
+                cookie.as_mut(),
+                Some(hook_trampoline),
+                callback.as_mut() as *mut HookCallback as *mut _,
+            );
+        }
+
+        AptHook {
+            cookie,
+            _callback: callback,
+            _apt: PhantomData,
+        }
+    }
+
+    /// Records the lifecycle events of the application in a queue, to be polled from the main loop.
+    pub fn event_queue(&self) -> AptEventQueue<'_> {
+        let events = Arc::new(Mutex::new(VecDeque::new()));
+        let sender = Arc::clone(&events);
+
+        let hook = self.hook(move |event| {
+            if let Ok(mut events) = sender.lock() {
+                events.push_back(event);
+            }
+        });
+
+        AptEventQueue {
+            events,
+            _hook: hook,
+        }
+    }
+
+    /// Registers a closure to be called when another applet sends a message to the application.
+    ///
+    /// The closure receives the sender's [`AppId`] and the message payload. Only one message callback can be
+    /// registered at a time: registering a new one replaces the previous one.
+    ///
+    /// # Notes
+    ///
+    /// A panic inside the closure aborts the program, since it can't unwind through libctru.
+    pub fn set_message_callback<F>(&self, callback: F) -> MessageCallback<'_>
+    where
+        F: FnMut(AppId, &[u8]) + Send + 'static,
+    {
+        let mut callback: Box<MessageCallbackFn> = Box::new(Box::new(callback));
+        let user = callback.as_mut() as *mut MessageCallbackFn;
+
+        CURRENT_MESSAGE_CALLBACK.store(user, Ordering::SeqCst);
+        unsafe { ctru_sys::aptSetMessageCallback(Some(message_trampoline), user as *mut _) };
+
+        MessageCallback {
+            callback,
+            _apt: PhantomData,
+        }
+    }
+}
+
+impl TitleId {
+    /// Returns the upper half of the ID, which identifies the kind of title (application, system applet, DLC...).
+    pub fn high(self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+
+    /// Returns the lower half of the ID, which identifies the title itself.
+    pub fn low(self) -> u32 {
+        self.0 as u32
+    }
+}
+
+impl From<u64> for TitleId {
+    fn from(id: u64) -> Self {
+        TitleId(id)
+    }
+}
+
+impl fmt::Display for TitleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016X}", self.0)
+    }
+}
+
+impl DeliverArg {
+    /// Returns the ID of the title which launched the application.
+    pub fn sender(&self) -> TitleId {
+        self.sender
+    }
+
+    /// Returns the parameter sent by the launching title.
+    ///
+    /// The size of the parameter isn't transmitted, so this is always [`DELIVER_ARG_PARAM_SIZE`] bytes long,
+    /// padded with zeros.
+    pub fn param(&self) -> &[u8] {
+        &self.param
+    }
+
+    /// Returns the HMAC sent by the launching title.
+    pub fn hmac(&self) -> &[u8; DELIVER_ARG_HMAC_SIZE] {
+        &self.hmac
+    }
+}
+
+impl Parameter {
+    /// Returns the applet which sent the parameter.
+    pub fn sender(&self) -> AppId {
+        self.sender
+    }
+
+    /// Returns the command carried by the parameter.
+    pub fn command(&self) -> AptCommand {
+        self.command
+    }
+
+    /// Returns the payload of the parameter.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Returns the handle sent with the parameter, if any.
+    pub fn handle(&self) -> Option<&ParameterHandle> {
+        self.handle.as_ref()
+    }
+
+    /// Takes ownership of the handle sent with the parameter, if any.
+    pub fn take_handle(&mut self) -> Option<ParameterHandle> {
+        self.handle.take()
+    }
+}
+
+impl ParameterHandle {
+    /// Wraps a raw kernel handle.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid handle, not owned by anything else: it's closed when the
+    /// returned value is dropped.
+    pub unsafe fn from_raw(handle: ctru_sys::Handle) -> Self {
+        ParameterHandle(handle)
+    }
+
+    /// Returns the raw kernel handle, which stays owned by `self`.
+    pub fn as_raw(&self) -> ctru_sys::Handle {
+        self.0
+    }
+
+    /// Returns the raw kernel handle, leaving the caller in charge of closing it.
+    pub fn into_raw(self) -> ctru_sys::Handle {
+        let handle = self.0;
+        std::mem::forget(self);
+        handle
+    }
+}
+
+impl Drop for ParameterHandle {
+    fn drop(&mut self) {
+        // Closing a handle we own can't fail in a way we could do anything about.
+        let _ = unsafe { ctru_sys::svcCloseHandle(self.0) };
+    }
 }
 
 impl Drop for Apt {
@@ -27,3 +673,258 @@ impl Drop for Apt {
         unsafe { ctru_sys::aptExit() };
     }
 }
+
+impl Drop for HomePolicyGuard<'_> {
+    fn drop(&mut self) {
+        unsafe { ctru_sys::aptSetHomeAllowed(self.previous) };
+    }
+}
+
+impl Drop for SleepPolicyGuard<'_> {
+    fn drop(&mut self) {
+        unsafe { ctru_sys::aptSetSleepAllowed(self.previous) };
+    }
+}
+
+impl AptEventQueue<'_> {
+    /// Returns the oldest event not read yet, if any.
+    pub fn poll(&self) -> Option<AptEvent> {
+        self.events.lock().ok()?.pop_front()
+    }
+
+    /// Returns all the events not read yet, oldest first.
+    pub fn drain(&self) -> Vec<AptEvent> {
+        match self.events.lock() {
+            Ok(mut events) => events.drain(..).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl Drop for AptHook<'_> {
+    fn drop(&mut self) {
+        unsafe { ctru_sys::aptUnhook(self.cookie.as_mut()) };
+    }
+}
+
+impl Drop for MessageCallback<'_> {
+    fn drop(&mut self) {
+        let user = self.callback.as_mut() as *mut MessageCallbackFn;
+
+        // Only unregister the callback if it wasn't replaced by a newer one in the meantime.
+        if CURRENT_MESSAGE_CALLBACK
+            .compare_exchange(user, ptr::null_mut(), Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            unsafe { ctru_sys::aptSetMessageCallback(None, ptr::null_mut()) };
+        }
+    }
+}
+
+/// Time between two checks in [`Apt::receive_parameter_timeout`].
+const PARAMETER_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+type ReadParameterFn = unsafe extern "C" fn(
+    ctru_sys::NS_APPID,
+    *mut libc::c_void,
+    usize,
+    *mut ctru_sys::NS_APPID,
+    *mut ctru_sys::APT_Command,
+    *mut usize,
+    *mut ctru_sys::Handle,
+) -> ctru_sys::Result;
+
+/// Calls `APT_ReceiveParameter` or `APT_GlanceParameter`, which share the same signature.
+fn read_parameter(read: ReadParameterFn, app: AppId) -> crate::Result<Option<Parameter>> {
+    let mut payload = vec![0; PARAMETER_MAX_SIZE];
+    let mut sender = ctru_sys::APPID_NONE;
+    let mut command = ctru_sys::APTCMD_NONE;
+    let mut size = 0;
+    let mut handle = 0;
+
+    let result = unsafe {
+        read(
+            app.into(),
+            payload.as_mut_ptr() as *mut _,
+            payload.len(),
+            &mut sender,
+            &mut command,
+            &mut size,
+            &mut handle,
+        )
+    };
+
+    // APT replies with a "no data" error when no parameter is pending.
+    if ctru_sys::R_DESCRIPTION(result) == ctru_sys::RD_NO_DATA as i32 {
+        return Ok(None);
+    }
+    ResultCode(result)?;
+
+    payload.truncate(size);
+
+    Ok(Some(Parameter {
+        sender: sender.into(),
+        command: command.into(),
+        payload,
+        handle: (handle != 0).then_some(ParameterHandle(handle)),
+    }))
+}
+
+unsafe extern "C" fn hook_trampoline(hook: ctru_sys::APT_HookType, param: *mut libc::c_void) {
+    // Safety: `param` points to the callback owned by the `AptHook`, which unregisters itself before dropping it.
+    let callback = &mut *(param as *mut HookCallback);
+
+    if let Ok(event) = AptEvent::try_from(hook) {
+        call_or_abort(|| callback(event));
+    }
+}
+
+unsafe extern "C" fn message_trampoline(
+    user: *mut libc::c_void,
+    sender: ctru_sys::NS_APPID,
+    msg: *mut libc::c_void,
+    msgsize: usize,
+) {
+    // Safety: `user` points to the callback owned by the `MessageCallback`, which unregisters itself before dropping it.
+    let callback = &mut *(user as *mut MessageCallbackFn);
+    let message: &[u8] = if msg.is_null() || msgsize == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(msg as *const u8, msgsize)
+    };
+
+    call_or_abort(|| callback(sender.into(), message));
+}
+
+/// Unwinding into C code is undefined behaviour, so panics in the user's callbacks abort the program instead.
+/// The panic message has already been printed by the panic hook at that point.
+fn call_or_abort(f: impl FnOnce()) {
+    if panic::catch_unwind(AssertUnwindSafe(f)).is_err() {
+        std::process::abort();
+    }
+}
+
+impl TryFrom<ctru_sys::APT_HookType> for AptEvent {
+    type Error = ();
+
+    fn try_from(value: ctru_sys::APT_HookType) -> Result<Self, Self::Error> {
+        match value {
+            ctru_sys::APTHOOK_ONSUSPEND => Ok(AptEvent::Suspend),
+            ctru_sys::APTHOOK_ONRESTORE => Ok(AptEvent::Restore),
+            ctru_sys::APTHOOK_ONSLEEP => Ok(AptEvent::Sleep),
+            ctru_sys::APTHOOK_ONWAKEUP => Ok(AptEvent::WakeUp),
+            ctru_sys::APTHOOK_ONEXIT => Ok(AptEvent::Exit),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<ctru_sys::NS_APPID> for AppId {
+    fn from(id: ctru_sys::NS_APPID) -> Self {
+        match id {
+            ctru_sys::APPID_NONE => AppId::None,
+            ctru_sys::APPID_HOMEMENU => AppId::HomeMenu,
+            ctru_sys::APPID_CAMERA => AppId::Camera,
+            ctru_sys::APPID_FRIENDS_LIST => AppId::FriendsList,
+            ctru_sys::APPID_GAME_NOTES => AppId::GameNotes,
+            ctru_sys::APPID_WEB => AppId::Web,
+            ctru_sys::APPID_INSTRUCTION_MANUAL => AppId::InstructionManual,
+            ctru_sys::APPID_NOTIFICATIONS => AppId::Notifications,
+            ctru_sys::APPID_MIIVERSE => AppId::Miiverse,
+            ctru_sys::APPID_MIIVERSE_POSTING => AppId::MiiversePosting,
+            ctru_sys::APPID_AMIIBO_SETTINGS => AppId::AmiiboSettings,
+            ctru_sys::APPID_APPLICATION => AppId::Application,
+            ctru_sys::APPID_ESHOP => AppId::Eshop,
+            ctru_sys::APPID_SOFTWARE_KEYBOARD => AppId::SoftwareKeyboard,
+            ctru_sys::APPID_APPLETED => AppId::AppletEd,
+            ctru_sys::APPID_PNOTE_AP => AppId::PNoteAp,
+            ctru_sys::APPID_SNOTE_AP => AppId::SNoteAp,
+            ctru_sys::APPID_ERROR => AppId::Error,
+            ctru_sys::APPID_MINT => AppId::Mint,
+            ctru_sys::APPID_EXTRAPAD => AppId::ExtraPad,
+            ctru_sys::APPID_MEMOLIB => AppId::Memolib,
+            other => AppId::Unknown(other),
+        }
+    }
+}
+
+impl From<AppId> for ctru_sys::NS_APPID {
+    fn from(id: AppId) -> Self {
+        match id {
+            AppId::None => ctru_sys::APPID_NONE,
+            AppId::HomeMenu => ctru_sys::APPID_HOMEMENU,
+            AppId::Camera => ctru_sys::APPID_CAMERA,
+            AppId::FriendsList => ctru_sys::APPID_FRIENDS_LIST,
+            AppId::GameNotes => ctru_sys::APPID_GAME_NOTES,
+            AppId::Web => ctru_sys::APPID_WEB,
+            AppId::InstructionManual => ctru_sys::APPID_INSTRUCTION_MANUAL,
+            AppId::Notifications => ctru_sys::APPID_NOTIFICATIONS,
+            AppId::Miiverse => ctru_sys::APPID_MIIVERSE,
+            AppId::MiiversePosting => ctru_sys::APPID_MIIVERSE_POSTING,
+            AppId::AmiiboSettings => ctru_sys::APPID_AMIIBO_SETTINGS,
+            AppId::Application => ctru_sys::APPID_APPLICATION,
+            AppId::Eshop => ctru_sys::APPID_ESHOP,
+            AppId::SoftwareKeyboard => ctru_sys::APPID_SOFTWARE_KEYBOARD,
+            AppId::AppletEd => ctru_sys::APPID_APPLETED,
+            AppId::PNoteAp => ctru_sys::APPID_PNOTE_AP,
+            AppId::SNoteAp => ctru_sys::APPID_SNOTE_AP,
+            AppId::Error => ctru_sys::APPID_ERROR,
+            AppId::Mint => ctru_sys::APPID_MINT,
+            AppId::ExtraPad => ctru_sys::APPID_EXTRAPAD,
+            AppId::Memolib => ctru_sys::APPID_MEMOLIB,
+            AppId::Unknown(other) => other,
+        }
+    }
+}
+
+impl From<ctru_sys::APT_Command> for AptCommand {
+    fn from(command: ctru_sys::APT_Command) -> Self {
+        match command {
+            ctru_sys::APTCMD_NONE => AptCommand::None,
+            ctru_sys::APTCMD_WAKEUP => AptCommand::WakeUp,
+            ctru_sys::APTCMD_REQUEST => AptCommand::Request,
+            ctru_sys::APTCMD_RESPONSE => AptCommand::Response,
+            ctru_sys::APTCMD_EXIT => AptCommand::Exit,
+            ctru_sys::APTCMD_MESSAGE => AptCommand::Message,
+            ctru_sys::APTCMD_HOMEBUTTON_ONCE => AptCommand::HomeButtonOnce,
+            ctru_sys::APTCMD_HOMEBUTTON_TWICE => AptCommand::HomeButtonTwice,
+            ctru_sys::APTCMD_DSP_SLEEP => AptCommand::DspSleep,
+            ctru_sys::APTCMD_DSP_WAKEUP => AptCommand::DspWakeUp,
+            ctru_sys::APTCMD_WAKEUP_EXIT => AptCommand::WakeUpExit,
+            ctru_sys::APTCMD_WAKEUP_PAUSE => AptCommand::WakeUpPause,
+            ctru_sys::APTCMD_WAKEUP_CANCEL => AptCommand::WakeUpCancel,
+            ctru_sys::APTCMD_WAKEUP_CANCELALL => AptCommand::WakeUpCancelAll,
+            ctru_sys::APTCMD_WAKEUP_POWERBUTTON => AptCommand::WakeUpPowerButton,
+            ctru_sys::APTCMD_WAKEUP_JUMPTOHOME => AptCommand::WakeUpJumpToHome,
+            ctru_sys::APTCMD_SYSAPPLET_REQUEST => AptCommand::SysAppletRequest,
+            ctru_sys::APTCMD_WAKEUP_LAUNCHAPP => AptCommand::WakeUpLaunchApp,
+            other => AptCommand::Unknown(other),
+        }
+    }
+}
+
+impl From<AptCommand> for ctru_sys::APT_Command {
+    fn from(command: AptCommand) -> Self {
+        match command {
+            AptCommand::None => ctru_sys::APTCMD_NONE,
+            AptCommand::WakeUp => ctru_sys::APTCMD_WAKEUP,
+            AptCommand::Request => ctru_sys::APTCMD_REQUEST,
+            AptCommand::Response => ctru_sys::APTCMD_RESPONSE,
+            AptCommand::Exit => ctru_sys::APTCMD_EXIT,
+            AptCommand::Message => ctru_sys::APTCMD_MESSAGE,
+            AptCommand::HomeButtonOnce => ctru_sys::APTCMD_HOMEBUTTON_ONCE,
+            AptCommand::HomeButtonTwice => ctru_sys::APTCMD_HOMEBUTTON_TWICE,
+            AptCommand::DspSleep => ctru_sys::APTCMD_DSP_SLEEP,
+            AptCommand::DspWakeUp => ctru_sys::APTCMD_DSP_WAKEUP,
+            AptCommand::WakeUpExit => ctru_sys::APTCMD_WAKEUP_EXIT,
+            AptCommand::WakeUpPause => ctru_sys::APTCMD_WAKEUP_PAUSE,
+            AptCommand::WakeUpCancel => ctru_sys::APTCMD_WAKEUP_CANCEL,
+            AptCommand::WakeUpCancelAll => ctru_sys::APTCMD_WAKEUP_CANCELALL,
+            AptCommand::WakeUpPowerButton => ctru_sys::APTCMD_WAKEUP_POWERBUTTON,
+            AptCommand::WakeUpJumpToHome => ctru_sys::APTCMD_WAKEUP_JUMPTOHOME,
+            AptCommand::SysAppletRequest => ctru_sys::APTCMD_SYSAPPLET_REQUEST,
+            AptCommand::WakeUpLaunchApp => ctru_sys::APTCMD_WAKEUP_LAUNCHAPP,
+            AptCommand::Unknown(other) => other,
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Configuration blocks
+//!
+//! The system configuration is stored as blocks of data, identified by an ID. This module contains the IDs
+//! of the blocks readable through [`Cfgu`](super::Cfgu), and the parsers of their content.
+//!
+//! | ID           | Size | Content                     | Parsed as                            |
+//! |--------------|------|-----------------------------|--------------------------------------|
+//! | `0x00070001` | 0x1  | Sound output mode           | [`SoundOutputMode`]                  |
+//! | `0x000A0000` | 0x1C | User name                   | [`parse_user_name`]                  |
+//! | `0x000A0001` | 0x2  | User birthday               | [`Birthday`]                         |
+//! | `0x000B0000` | 0x4  | User country and state      | [`CountryInfo`]                      |
+//! | `0x000C0000` | 0xC0 | Parental control settings   | [`ParentalRestrictions`]             |
+//!
+//! Source for the layouts: <https://www.3dbrew.org/wiki/Config_Savegame>
+
+use bitflags::bitflags;
+
+/// ID of the sound output mode block
+pub const SOUND_OUTPUT_MODE: u32 = 0x0007_0001;
+/// ID of the user name block
+pub const USER_NAME: u32 = 0x000A_0000;
+/// ID of the birthday block
+pub const BIRTHDAY: u32 = 0x000A_0001;
+/// ID of the country info block
+pub const COUNTRY_INFO: u32 = 0x000B_0000;
+/// ID of the parental controls block
+pub const PARENTAL_CONTROLS: u32 = 0x000C_0000;
+
+/// Size of the sound output mode block
+pub const SOUND_OUTPUT_MODE_SIZE: usize = 0x1;
+/// Size of the user name block
+pub const USER_NAME_SIZE: usize = 0x1C;
+/// Size of the birthday block
+pub const BIRTHDAY_SIZE: usize = 0x2;
+/// Size of the country info block
+pub const COUNTRY_INFO_SIZE: usize = 0x4;
+/// Size of the parental controls block
+pub const PARENTAL_CONTROLS_SIZE: usize = 0xC0;
+
+/// Represents the sound output mode set in the System Settings
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SoundOutputMode {
+    Mono,
+    Stereo,
+    Surround,
+    /// A value not known by this crate
+    Unknown(u8),
+}
+
+/// Represents the birthday of the user
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Birthday {
+    pub month: u8,
+    pub day: u8,
+}
+
+/// Represents the country and state (or province) of the user
+///
+/// The country code can be turned into a string with
+/// [`Cfgu::get_country_code_string`](super::Cfgu::get_country_code_string).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CountryInfo {
+    pub country_code: u8,
+    pub state_code: u8,
+}
+
+bitflags! {
+    /// Features restricted by the parental controls
+    pub struct ParentalRestrictions: u32 {
+        /// Parental controls are enabled. The other flags are only meaningful if this one is set
+        const ENABLED = 1 << 0;
+        const INTERNET_BROWSER = 1 << 1;
+        /// Display of 3D images
+        const DISPLAY_3D = 1 << 2;
+        /// Sharing images, audio, video and long text data
+        const SHARING = 1 << 3;
+        const ONLINE_INTERACTION = 1 << 4;
+        const STREETPASS = 1 << 5;
+        const FRIEND_REGISTRATION = 1 << 6;
+        const DS_DOWNLOAD_PLAY = 1 << 7;
+        const SHOPPING = 1 << 8;
+        /// Viewing distributed videos
+        const DISTRIBUTED_VIDEO = 1 << 9;
+        const MIIVERSE_VIEW = 1 << 10;
+        const MIIVERSE_POST = 1 << 11;
+        /// Child Online Privacy Protection
+        const COPPACS = 1 << 31;
+    }
+}
+
+impl SoundOutputMode {
+    /// Parses the content of the sound output mode block.
+    pub fn from_bytes(data: &[u8; SOUND_OUTPUT_MODE_SIZE]) -> Self {
+        match data[0] {
+            0 => SoundOutputMode::Mono,
+            1 => SoundOutputMode::Stereo,
+            2 => SoundOutputMode::Surround,
+            value => SoundOutputMode::Unknown(value),
+        }
+    }
+}
+
+impl Birthday {
+    /// Parses the content of the birthday block.
+    pub fn from_bytes(data: &[u8; BIRTHDAY_SIZE]) -> Self {
+        Birthday {
+            month: data[0],
+            day: data[1],
+        }
+    }
+}
+
+impl CountryInfo {
+    /// Parses the content of the country info block.
+    pub fn from_bytes(data: &[u8; COUNTRY_INFO_SIZE]) -> Self {
+        CountryInfo {
+            state_code: data[2],
+            country_code: data[3],
+        }
+    }
+}
+
+impl ParentalRestrictions {
+    /// Parses the restrictions from the content of the parental controls block.
+    ///
+    /// The rest of the block, which holds the PIN and the secret answer, is ignored.
+    pub fn from_bytes(data: &[u8; PARENTAL_CONTROLS_SIZE]) -> Self {
+        Self::from_bits_truncate(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+    }
+}
+
+/// Parses the content of the user name block.
+pub fn parse_user_name(data: &[u8; USER_NAME_SIZE]) -> String {
+    // The name is at most 10 UTF-16 characters, followed by a NUL terminator.
+    let name = data[..0x16]
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .take_while(|&c| c != 0)
+        .collect::<Vec<u16>>();
+
+    String::from_utf16_lossy(&name)
+}
+
+/// Parses a country code string, as returned by `CFGU_GetCountryCodeString`.
+pub fn parse_country_code_string(raw: u16) -> String {
+    String::from_utf8_lossy(&raw.to_le_bytes()).into_owned()
+}
+
+/// Builds a country code string for `CFGU_GetCountryCodeID`.
+pub fn make_country_code_string(code: [u8; 2]) -> u16 {
+    u16::from_le_bytes(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_name() {
+        let mut data = [0; USER_NAME_SIZE];
+        for (chunk, c) in data.chunks_exact_mut(2).zip("Ferris".encode_utf16()) {
+            chunk.copy_from_slice(&c.to_le_bytes());
+        }
+        // Leftovers after the terminator aren't part of the name.
+        data[0x18] = b'X';
+        assert_eq!(parse_user_name(&data), "Ferris");
+
+        // A full length name has no terminator in the first 0x14 bytes.
+        let mut data = [0; USER_NAME_SIZE];
+        for (chunk, c) in data.chunks_exact_mut(2).zip("ABCDEFGHIJ".encode_utf16()) {
+            chunk.copy_from_slice(&c.to_le_bytes());
+        }
+        assert_eq!(parse_user_name(&data), "ABCDEFGHIJ");
+    }
+
+    #[test]
+    fn small_blocks() {
+        assert_eq!(
+            Birthday::from_bytes(&[12, 25]),
+            Birthday { month: 12, day: 25 }
+        );
+        assert_eq!(
+            CountryInfo::from_bytes(&[0, 0, 2, 49]),
+            CountryInfo {
+                country_code: 49,
+                state_code: 2
+            }
+        );
+        assert_eq!(SoundOutputMode::from_bytes(&[2]), SoundOutputMode::Surround);
+        assert_eq!(
+            SoundOutputMode::from_bytes(&[9]),
+            SoundOutputMode::Unknown(9)
+        );
+    }
+
+    #[test]
+    fn parental_restrictions() {
+        let mut data = [0xAA; PARENTAL_CONTROLS_SIZE];
+        data[..4].copy_from_slice(&0x8000_0023u32.to_le_bytes());
+
+        let restrictions = ParentalRestrictions::from_bytes(&data);
+        assert_eq!(
+            restrictions,
+            ParentalRestrictions::ENABLED
+                | ParentalRestrictions::INTERNET_BROWSER
+                | ParentalRestrictions::STREETPASS
+                | ParentalRestrictions::COPPACS
+        );
+    }
+
+    #[test]
+    fn country_code_strings() {
+        let raw = make_country_code_string(*b"US");
+        assert_eq!(parse_country_code_string(raw), "US");
+        assert_eq!(raw, 0x5355);
+    }
+}
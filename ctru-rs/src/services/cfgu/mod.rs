@@ -0,0 +1,336 @@
+//! Configuration service
+//!
+//! This module contains basic methods to retrieve and change configuration from the console.
+//!
+//! The user's profile and settings are read from the configuration blocks listed in [`blocks`].
+
+use crate::error::ResultCode;
+
+pub mod blocks;
+
+pub use self::blocks::{Birthday, CountryInfo, ParentalRestrictions, SoundOutputMode};
+
+/// Represents the region of the console
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Region {
+    Japan,
+    USA,
+    Europe,
+    Australia,
+    China,
+    Korea,
+    Taiwan,
+    /// A value not known by this crate
+    Unknown(u8),
+}
+
+/// Represents the language of the console
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Language {
+    Japanese,
+    English,
+    French,
+    German,
+    Italian,
+    Spanish,
+    SimplifiedChinese,
+    Korean,
+    Dutch,
+    Portuguese,
+    Russian,
+    TraditionalChinese,
+    /// A value not known by this crate
+    Unknown(u8),
+}
+
+/// Represents the model of the console
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SystemModel {
+    Model3DS,
+    Model3DSXL,
+    ModelNew3DS,
+    Model2DS,
+    ModelNew3DSXL,
+    ModelNew2DSXL,
+    /// A value not known by this crate, e.g. a development unit
+    Unknown(u8),
+}
+
+/// Diagonal size of the screens of a console, in inches
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScreenSizes {
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl Language {
+    /// Returns the BCP 47 tag of the language, e.g. `"en"` or `"zh-Hant"`
+    ///
+    /// Unknown languages give `"und"`, the tag for an undetermined language.
+    pub fn bcp47_tag(self) -> &'static str {
+        match self {
+            Language::Japanese => "ja",
+            Language::English => "en",
+            Language::French => "fr",
+            Language::German => "de",
+            Language::Italian => "it",
+            Language::Spanish => "es",
+            Language::SimplifiedChinese => "zh-Hans",
+            Language::Korean => "ko",
+            Language::Dutch => "nl",
+            Language::Portuguese => "pt",
+            Language::Russian => "ru",
+            Language::TraditionalChinese => "zh-Hant",
+            Language::Unknown(_) => "und",
+        }
+    }
+}
+
+impl SystemModel {
+    /// Checks if the model is part of the New 3DS family (New 3DS, New 3DS XL, New 2DS XL)
+    ///
+    /// Unknown models are assumed not to be.
+    pub fn is_new_3ds(self) -> bool {
+        matches!(
+            self,
+            SystemModel::ModelNew3DS | SystemModel::ModelNew3DSXL | SystemModel::ModelNew2DSXL
+        )
+    }
+
+    /// Checks if the model can display stereoscopic 3D
+    ///
+    /// Unknown models are assumed to have a 3D screen, like the original 3DS.
+    pub fn has_3d_screen(self) -> bool {
+        !matches!(self, SystemModel::Model2DS | SystemModel::ModelNew2DSXL)
+    }
+
+    /// Checks if the model has a C-Stick and the ZL/ZR buttons
+    pub fn has_c_stick(self) -> bool {
+        self.is_new_3ds()
+    }
+
+    /// Returns the size of the screens of the model, or `None` for unknown models
+    pub fn screen_sizes(self) -> Option<ScreenSizes> {
+        let (top, bottom) = match self {
+            SystemModel::Model3DS | SystemModel::Model2DS => (3.53, 3.02),
+            SystemModel::ModelNew3DS => (3.88, 3.33),
+            SystemModel::Model3DSXL | SystemModel::ModelNew3DSXL | SystemModel::ModelNew2DSXL => {
+                (4.88, 4.18)
+            }
+            SystemModel::Unknown(_) => return None,
+        };
+
+        Some(ScreenSizes { top, bottom })
+    }
+}
+
+/// Represents the configuration service. No actions can be performed
+/// until an instance of this struct is created.
+///
+/// The service exits when all instances of this struct go out of scope.
+pub struct Cfgu(());
+
+impl Cfgu {
+    /// Initializes the CFGU service.
+    ///
+    /// # Errors
+    ///
+    /// This function will return Err if there was an error initializing the
+    /// CFGU service.
+    ///
+    /// ctrulib services are reference counted, so this function may be called
+    /// as many times as desired and the service will not exit until all
+    /// instances of Cfgu drop out of scope.
+    pub fn init() -> crate::Result<Cfgu> {
+        ResultCode(unsafe { ctru_sys::cfguInit() })?;
+        Ok(Cfgu(()))
+    }
+
+    /// Gets system region from secure info
+    pub fn get_region(&self) -> crate::Result<Region> {
+        let mut region: u8 = 0;
+
+        ResultCode(unsafe { ctru_sys::CFGU_SecureInfoGetRegion(&mut region) })?;
+        Ok(region.into())
+    }
+
+    /// Gets system's model
+    pub fn get_model(&self) -> crate::Result<SystemModel> {
+        let mut model: u8 = 0;
+
+        ResultCode(unsafe { ctru_sys::CFGU_GetSystemModel(&mut model) })?;
+        Ok(model.into())
+    }
+
+    /// Gets system's language
+    pub fn get_language(&self) -> crate::Result<Language> {
+        let mut language: u8 = 0;
+
+        ResultCode(unsafe { ctru_sys::CFGU_GetSystemLanguage(&mut language) })?;
+        Ok(language.into())
+    }
+
+    /// Checks if NFC is supported by the console
+    pub fn is_nfc_supported(&self) -> crate::Result<bool> {
+        let mut supported: bool = false;
+
+        ResultCode(unsafe { ctru_sys::CFGU_IsNFCSupported(&mut supported) })?;
+        Ok(supported)
+    }
+
+    /// Check if the console is from the 2DS family (2DS, New2DS, New2DSXL)
+    pub fn is_2ds_family(&self) -> crate::Result<bool> {
+        let mut is_2ds_family: u8 = 0;
+
+        ResultCode(unsafe { ctru_sys::CFGU_GetModelNintendo2DS(&mut is_2ds_family) })?;
+        Ok(is_2ds_family == 0)
+    }
+
+    /// Checks if the console is from the USA region, and set to either Canada or the USA
+    pub fn is_region_canada_usa(&self) -> crate::Result<bool> {
+        let mut value: u8 = 0;
+
+        ResultCode(unsafe { ctru_sys::CFGU_GetRegionCanadaUSA(&mut value) })?;
+        Ok(value != 0)
+    }
+
+    /// Gets an hash unique to the console, salted with `salt`
+    ///
+    /// Only the lowest 20 bits of the salt are used. Applications usually pass their unique ID,
+    /// so that the hash can't be linked to the ones generated by other applications.
+    pub fn get_console_unique_hash(&self, salt: u32) -> crate::Result<u64> {
+        let mut hash: u64 = 0;
+
+        ResultCode(unsafe { ctru_sys::CFGU_GenHashConsoleUnique(salt, &mut hash) })?;
+        Ok(hash)
+    }
+
+    /// Gets the two-letter string of a country code, e.g. `"JP"` for the code 1
+    pub fn get_country_code_string(&self, code: u16) -> crate::Result<String> {
+        let mut raw: u16 = 0;
+
+        ResultCode(unsafe { ctru_sys::CFGU_GetCountryCodeString(code, &mut raw) })?;
+        Ok(blocks::parse_country_code_string(raw))
+    }
+
+    /// Gets the country code of a two-letter string, e.g. `1` for `*b"JP"`
+    pub fn get_country_code_id(&self, string: [u8; 2]) -> crate::Result<u16> {
+        let mut code: u16 = 0;
+
+        ResultCode(unsafe {
+            ctru_sys::CFGU_GetCountryCodeID(blocks::make_country_code_string(string), &mut code)
+        })?;
+        Ok(code)
+    }
+
+    /// Gets the name of the user
+    pub fn get_user_name(&self) -> crate::Result<String> {
+        let data = self.get_config_block::<{ blocks::USER_NAME_SIZE }>(blocks::USER_NAME)?;
+        Ok(blocks::parse_user_name(&data))
+    }
+
+    /// Gets the birthday of the user
+    pub fn get_birthday(&self) -> crate::Result<Birthday> {
+        let data = self.get_config_block::<{ blocks::BIRTHDAY_SIZE }>(blocks::BIRTHDAY)?;
+        Ok(Birthday::from_bytes(&data))
+    }
+
+    /// Gets the country and state of the user
+    pub fn get_country_info(&self) -> crate::Result<CountryInfo> {
+        let data = self.get_config_block::<{ blocks::COUNTRY_INFO_SIZE }>(blocks::COUNTRY_INFO)?;
+        Ok(CountryInfo::from_bytes(&data))
+    }
+
+    /// Gets the sound output mode
+    pub fn get_sound_output_mode(&self) -> crate::Result<SoundOutputMode> {
+        let data =
+            self.get_config_block::<{ blocks::SOUND_OUTPUT_MODE_SIZE }>(blocks::SOUND_OUTPUT_MODE)?;
+        Ok(SoundOutputMode::from_bytes(&data))
+    }
+
+    /// Gets the features restricted by the parental controls
+    pub fn get_parental_restrictions(&self) -> crate::Result<ParentalRestrictions> {
+        let data =
+            self.get_config_block::<{ blocks::PARENTAL_CONTROLS_SIZE }>(blocks::PARENTAL_CONTROLS)?;
+        Ok(ParentalRestrictions::from_bytes(&data))
+    }
+
+    /// Reads the raw content of the configuration block `id`
+    ///
+    /// `N` must be the size of the block, see the table in [`blocks`].
+    pub fn get_config_block<const N: usize>(&self, id: u32) -> crate::Result<[u8; N]> {
+        let mut data = [0; N];
+
+        ResultCode(unsafe {
+            ctru_sys::CFGU_GetConfigInfoBlk2(N as u32, id, data.as_mut_ptr().cast())
+        })?;
+        Ok(data)
+    }
+}
+
+impl Drop for Cfgu {
+    fn drop(&mut self) {
+        unsafe {
+            ctru_sys::cfguExit();
+        }
+    }
+}
+
+macro_rules! u8_conversions {
+    ($type:ident, $($variant:ident = $value:path),* $(,)?) => {
+        impl From<u8> for $type {
+            fn from(value: u8) -> Self {
+                match u32::from(value) {
+                    $($value => $type::$variant,)*
+                    _ => $type::Unknown(value),
+                }
+            }
+        }
+
+        impl From<$type> for u8 {
+            fn from(value: $type) -> Self {
+                match value {
+                    $($type::$variant => $value as u8,)*
+                    $type::Unknown(value) => value,
+                }
+            }
+        }
+    };
+}
+
+u8_conversions!(
+    Region,
+    Japan = ctru_sys::CFG_REGION_JPN,
+    USA = ctru_sys::CFG_REGION_USA,
+    Europe = ctru_sys::CFG_REGION_EUR,
+    Australia = ctru_sys::CFG_REGION_AUS,
+    China = ctru_sys::CFG_REGION_CHN,
+    Korea = ctru_sys::CFG_REGION_KOR,
+    Taiwan = ctru_sys::CFG_REGION_TWN,
+);
+
+u8_conversions!(
+    Language,
+    Japanese = ctru_sys::CFG_LANGUAGE_JP,
+    English = ctru_sys::CFG_LANGUAGE_EN,
+    French = ctru_sys::CFG_LANGUAGE_FR,
+    German = ctru_sys::CFG_LANGUAGE_DE,
+    Italian = ctru_sys::CFG_LANGUAGE_IT,
+    Spanish = ctru_sys::CFG_LANGUAGE_ES,
+    SimplifiedChinese = ctru_sys::CFG_LANGUAGE_ZH,
+    Korean = ctru_sys::CFG_LANGUAGE_KO,
+    Dutch = ctru_sys::CFG_LANGUAGE_NL,
+    Portuguese = ctru_sys::CFG_LANGUAGE_PT,
+    Russian = ctru_sys::CFG_LANGUAGE_RU,
+    TraditionalChinese = ctru_sys::CFG_LANGUAGE_TW,
+);
+
+u8_conversions!(
+    SystemModel,
+    Model3DS = ctru_sys::CFG_MODEL_3DS,
+    Model3DSXL = ctru_sys::CFG_MODEL_3DSXL,
+    ModelNew3DS = ctru_sys::CFG_MODEL_N3DS,
+    Model2DS = ctru_sys::CFG_MODEL_2DS,
+    ModelNew3DSXL = ctru_sys::CFG_MODEL_N3DSXL,
+    ModelNew2DSXL = ctru_sys::CFG_MODEL_N2DSXL,
+);
@@ -1,19 +1,24 @@
 //! Filesystem service
 //!
 //! This module contains basic methods to manipulate the contents of the 3DS's filesystem.
-//! Only the SD card is currently supported.
+//! The SD card is opened with [`Fs::sdmc`], and other archives (save data, extdata...) with [`Fs::open_archive`].
+//! Archives can also be [mounted](Archive::mount) as devices, to be accessed through [`std::fs`].
 
+use crate::error::ResultCode;
+use crate::services::apt::TitleId;
 use bitflags::bitflags;
-use std::ffi::OsString;
+use std::ffi::{CString, OsString};
+use std::fmt;
 use std::io::Error as IoError;
 use std::io::ErrorKind as IoErrorKind;
 use std::io::Result as IoResult;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::mem;
+use std::ops::{ControlFlow, Deref};
 use std::path::{Path, PathBuf};
-use std::ptr;
 use std::slice;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use widestring::{WideCStr, WideCString};
 
 bitflags! {
@@ -34,16 +39,17 @@ bitflags! {
 }
 
 bitflags! {
+    /// Attributes of a file or directory, see [`Metadata::attributes`] and [`set_attributes`].
     #[derive(Default)]
-    struct FsAttribute: u32 {
-        const FS_ATTRIBUTE_DIRECTORY =        1;
-        const FS_ATTRIBUTE_HIDDEN    =      256;
-        const FS_ATTRIBUTE_ARCHIVE   =    65536;
-        const FS_ATTRIBUTE_READ_ONLY = 16777216;
+    pub struct FsAttribute: u32 {
+        const DIRECTORY =        1;
+        const HIDDEN    =      256;
+        const ARCHIVE   =    65536;
+        const READ_ONLY = 16777216;
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PathType {
     Invalid,
     Empty,
@@ -78,6 +84,25 @@ pub enum ArchiveID {
     DemoSavedata,
 }
 
+/// Type of the game card inserted in the card slot.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CardType {
+    /// 3DS game card
+    Ctr,
+    /// DS or DSi game card
+    Twl,
+    /// A value not known by this crate
+    Unknown(u32),
+}
+
+/// Storage media a title can be installed on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MediaType {
+    Nand,
+    Sd,
+    GameCard,
+}
+
 /// Represents the filesystem service. No file IO can be performed
 /// until an instance of this struct is created.
 ///
@@ -98,9 +123,155 @@ pub struct Fs(());
 /// ```
 pub struct Archive {
     id: ArchiveID,
+    path: ArchivePath,
     handle: u64,
 }
 
+/// Path identifying an archive to open, e.g. the title whose save data should be opened.
+///
+/// Most archives are identified by a binary path: a few integers, encoded in little-endian.
+/// The constructors below build the paths of the common archives, and [`ArchivePath::binary`]
+/// can build any other one.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ctru::services::fs::{ArchiveID, ArchivePath, Fs, MediaType};
+///
+/// let fs = Fs::init().unwrap();
+///
+/// // Same as `ArchivePath::extdata(MediaType::Sd, 0x1234)`
+/// let path = ArchivePath::binary()
+///     .media_type(MediaType::Sd)
+///     .u64(0x1234);
+/// let extdata = fs.open_archive(ArchiveID::Extdata, &path).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchivePath {
+    kind: PathType,
+    data: Vec<u8>,
+}
+
+/// Handle to an open save data archive.
+///
+/// Changes to save data are only written to the storage by [`SaveArchive::commit`]: until then,
+/// they're lost if the application exits or the console is turned off. Closing the archive does
+/// **not** commit them. [`SaveArchive::transaction`] returns a guard that commits on drop.
+///
+/// A `SaveArchive` can be used anywhere an [`Archive`] is expected.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::Write;
+/// use ctru::services::fs::{File, Fs};
+///
+/// let fs = Fs::init().unwrap();
+/// let save = fs.save_data().unwrap();
+///
+/// {
+///     let _transaction = save.transaction();
+///     let mut file = File::create(&save, "/progress.bin").unwrap();
+///     file.write_all(&[1, 2, 3]).unwrap();
+/// } // The changes are committed here
+/// ```
+pub struct SaveArchive {
+    archive: Archive,
+}
+
+/// Guard returned by [`SaveArchive::transaction`], which commits the save data when dropped.
+///
+/// Errors are ignored when committing on drop. Use [`CommitGuard::commit`] to handle them.
+#[must_use = "the save data is committed as soon as the guard is dropped"]
+pub struct CommitGuard<'a> {
+    save: &'a SaveArchive,
+}
+
+/// A device through which files can be accessed with [`std::fs`], with paths such as `name:/dir/file.txt`
+///
+/// Devices are the SD card ([`Fs::sdmc_device`]), the [RomFS](crate::romfs::RomFS) and the
+/// [mounted archives](Archive::mount).
+pub trait Device {
+    /// Returns the name of the device, e.g. `sdmc`.
+    fn name(&self) -> &str;
+
+    /// Returns the root directory of the device, e.g. `sdmc:/`.
+    fn root(&self) -> PathBuf {
+        PathBuf::from(format!("{}:/", self.name()))
+    }
+}
+
+/// The `sdmc` device, see [`Fs::sdmc_device`].
+#[derive(Copy, Clone, Debug)]
+pub struct SdmcDevice {
+    _private: (),
+}
+
+/// Guard returned by [`Archive::mount`], which unmounts the device when dropped.
+#[must_use = "the device is unmounted as soon as the guard is dropped"]
+pub struct MountGuard {
+    name: CString,
+}
+
+/// Layout of formatted save data, see [`SaveArchive::format`].
+///
+/// The sizes of the hash tables (buckets) are usually the maximum number of entries, rounded up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FormatParams {
+    /// Size of the save data, in blocks of 512 bytes. Ignored for titles installed on the SD card,
+    /// whose size comes from their header.
+    pub blocks: u32,
+    /// Maximum number of directories
+    pub directories: u32,
+    /// Maximum number of files
+    pub files: u32,
+    /// Size of the directory hash table
+    pub directory_buckets: u32,
+    /// Size of the file hash table
+    pub file_buckets: u32,
+    /// Whether to keep a second copy of the data, which makes commits atomic
+    pub duplicate_data: bool,
+}
+
+/// Space information of a storage media, see [`Fs::get_sdmc_info`] and [`Fs::get_nand_info`].
+///
+/// The [`Display`](fmt::Display) implementation prints the free and total space in a human-readable
+/// form, e.g. `1.2 GiB free of 3.7 GiB`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StorageInfo {
+    /// Size of a sector, in bytes
+    pub sector_size: u32,
+    /// Size of a cluster, in bytes
+    pub cluster_size: u32,
+    pub total_clusters: u32,
+    pub free_clusters: u32,
+}
+
+/// Number of bytes, displayed in a human-readable form with binary units (e.g. `1.5 MiB`).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteSize(pub u64);
+
+/// Speed settings of the SD card interface, see [`Fs::get_sdmc_speed_info`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SdmcSpeedInfo {
+    pub high_speed_mode_enabled: bool,
+    /// Whether a clock divider of 2 is used
+    pub uses_highest_clock_rate: bool,
+    /// Value of the `SD_CLK_CTRL` register
+    pub sd_clk_ctrl: u16,
+}
+
+/// Limits of new extdata, see [`Fs::create_extdata`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExtdataParams {
+    /// Maximum number of directories
+    pub directories: u32,
+    /// Maximum number of files
+    pub files: u32,
+    /// Maximum size of the content, in bytes
+    pub size_limit: u64,
+}
+
 /// A reference to an open file on the filesystem.
 ///
 /// An instance of a `File` can be read and/or written to depending
@@ -163,12 +334,15 @@ pub struct File {
 /// Metadata information about a file.
 ///
 /// This structure is returned from the [`metadata`] function and
-/// represents known metadata about a file.
+/// represents known metadata about a file. It borrows the archive the file was looked up in,
+/// which [`Metadata::modified`] queries.
 ///
 /// [`metadata`]: fn.metadata.html
-pub struct Metadata {
+pub struct Metadata<'a> {
     attributes: u32,
     size: u64,
+    /// Archive and path to query the modification time of, if known
+    location: Option<(&'a Archive, PathBuf)>,
 }
 
 /// Options and flags which can be used to configure how a [`File`] is opened.
@@ -244,16 +418,26 @@ pub struct OpenOptions {
 /// [`read_dir`]: fn.read_dir.html
 /// [`DirEntry`]: struct.DirEntry.html
 ///
+/// Entries are read from the filesystem in batches, of [`DEFAULT_BATCH_SIZE`] entries unless
+/// configured with [`ReadDir::batch_size`].
+///
 /// # Errors
 ///
 /// This Result will return Err if there's some sort of intermittent IO error
-/// during iteration.
+/// during iteration. The iteration ends after an error.
 pub struct ReadDir<'a> {
     handle: Dir,
     root: Arc<PathBuf>,
     arch: &'a Archive,
+    batch: Vec<ctru_sys::FS_DirectoryEntry>,
+    batch_size: usize,
+    position: usize,
+    finished: bool,
 }
 
+/// Number of entries read at once by [`ReadDir`].
+pub const DEFAULT_BATCH_SIZE: usize = 32;
+
 /// Entries returned by the [`ReadDir`] iterator.
 ///
 /// [`ReadDir`]: struct.ReadDir.html
@@ -267,6 +451,70 @@ pub struct DirEntry<'a> {
     arch: &'a Archive,
 }
 
+/// Recursive iterator over the entries within a directory.
+///
+/// This iterator is returned from the [`walk_dir`] function of this module. Directories are
+/// yielded before their content, and the content of a directory is read only after the directory
+/// itself was yielded.
+pub struct WalkDir<'a> {
+    arch: &'a Archive,
+    root: Option<PathBuf>,
+    stack: Vec<ReadDir<'a>>,
+    min_depth: usize,
+    max_depth: usize,
+    filter: Option<EntryFilter<'a>>,
+}
+
+type EntryFilter<'a> = Box<dyn FnMut(&DirEntry<'a>) -> bool + 'a>;
+
+/// Entries returned by the [`WalkDir`] iterator.
+///
+/// It derefs to the [`DirEntry`] it was read from.
+pub struct WalkEntry<'a> {
+    entry: DirEntry<'a>,
+    depth: usize,
+}
+
+/// Progress of a copy, passed to the callback of [`copy_with_progress`] and
+/// [`copy_dir_all_with_progress`].
+#[derive(Copy, Clone, Debug)]
+pub struct CopyProgress<'p> {
+    /// Path of the file being copied, in the source archive
+    pub path: &'p Path,
+    /// Bytes of the current file copied so far
+    pub file_bytes_copied: u64,
+    /// Size of the current file
+    pub file_size: u64,
+    /// Bytes of all files copied so far
+    pub total_bytes_copied: u64,
+    /// Size of all files to copy
+    pub total_bytes: u64,
+}
+
+/// Error returned when the callback of [`copy_with_progress`] or [`copy_dir_all_with_progress`]
+/// cancelled the copy.
+///
+/// It is wrapped in an [`io::Error`](IoError) of kind [`Other`](IoErrorKind::Other), and can be
+/// told apart from the other errors by downcasting it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ctru::services::fs::{self, Cancelled, Fs};
+/// use std::ops::ControlFlow;
+///
+/// let fs = Fs::init().unwrap();
+/// let sdmc = fs.sdmc().unwrap();
+///
+/// match fs::copy_with_progress(&sdmc, "/big.bin", &sdmc, "/copy.bin", |_| ControlFlow::Break(())) {
+///     Ok(size) => println!("Copied {size} bytes"),
+///     Err(e) if e.get_ref().map_or(false, |e| e.is::<Cancelled>()) => println!("Cancelled"),
+///     Err(e) => println!("Copy failed: {e}"),
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cancelled;
+
 #[doc(hidden)]
 struct Dir(u32);
 
@@ -300,28 +548,495 @@ impl Fs {
     }
 
     /// Returns a handle to the SDMC (memory card) Archive.
+    ///
+    /// The SD card is also mounted as the `sdmc` device at startup, so it can be accessed
+    /// through [`std::fs`] with paths such as `sdmc:/dir/file.txt`, like a [mounted](Archive::mount)
+    /// archive.
     pub fn sdmc(&self) -> crate::Result<Archive> {
-        unsafe {
-            let mut handle = 0;
-            let id = ArchiveID::Sdmc;
-            let path = ctru_sys::fsMakePath(PathType::Empty.into(), ptr::null() as _);
-            let r = ctru_sys::FSUSER_OpenArchive(&mut handle, id.into(), path);
-            if r < 0 {
-                Err(crate::Error::from(r))
-            } else {
-                Ok(Archive { handle, id })
+        self.open_archive(ArchiveID::Sdmc, &ArchivePath::empty())
+    }
+
+    /// Returns the `sdmc` device, which the SD card is mounted as at startup.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ctru::services::fs::{Device, Fs};
+    ///
+    /// let fs = Fs::init().unwrap();
+    ///
+    /// let sdmc = fs.sdmc_device();
+    /// let entries = std::fs::read_dir(sdmc.root().join("3ds")).unwrap();
+    /// ```
+    pub fn sdmc_device(&self) -> SdmcDevice {
+        SdmcDevice { _private: () }
+    }
+
+    /// Opens the archive `id`, identified by `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive doesn't exist (e.g. the title has no
+    /// extdata) or if the application isn't allowed to access it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ctru::services::apt::TitleId;
+    /// use ctru::services::fs::{ArchiveID, ArchivePath, Fs, MediaType};
+    ///
+    /// let fs = Fs::init().unwrap();
+    /// let path = ArchivePath::user_save_data(MediaType::Sd, TitleId(0x0004000000055D00));
+    /// let save_data = fs.open_archive(ArchiveID::UserSavedata, &path).unwrap();
+    /// ```
+    pub fn open_archive(&self, id: ArchiveID, path: &ArchivePath) -> crate::Result<Archive> {
+        let mut handle = 0;
+
+        ResultCode(unsafe { ctru_sys::FSUSER_OpenArchive(&mut handle, id.into(), path.as_raw()) })?;
+        Ok(Archive {
+            id,
+            path: path.clone(),
+            handle,
+        })
+    }
+
+    /// Returns the space information of the SD card.
+    pub fn get_sdmc_info(&self) -> crate::Result<StorageInfo> {
+        let mut resource = ctru_sys::FS_ArchiveResource::default();
+
+        ResultCode(unsafe { ctru_sys::FSUSER_GetSdmcArchiveResource(&mut resource) })?;
+        Ok(resource.into())
+    }
+
+    /// Returns the space information of the NAND.
+    pub fn get_nand_info(&self) -> crate::Result<StorageInfo> {
+        let mut resource = ctru_sys::FS_ArchiveResource::default();
+
+        ResultCode(unsafe { ctru_sys::FSUSER_GetNandArchiveResource(&mut resource) })?;
+        Ok(resource.into())
+    }
+
+    /// Returns whether an SD card is inserted.
+    pub fn is_sdmc_detected(&self) -> crate::Result<bool> {
+        let mut detected = false;
+
+        ResultCode(unsafe { ctru_sys::FSUSER_IsSdmcDetected(&mut detected) })?;
+        Ok(detected)
+    }
+
+    /// Returns whether the SD card can be written to, i.e. it isn't write-protected.
+    pub fn is_sdmc_writable(&self) -> crate::Result<bool> {
+        let mut writable = false;
+
+        ResultCode(unsafe { ctru_sys::FSUSER_IsSdmcWritable(&mut writable) })?;
+        Ok(writable)
+    }
+
+    /// Returns the CID (Card IDentification register) of the SD card.
+    pub fn get_sdmc_cid(&self) -> crate::Result<[u8; 16]> {
+        let mut cid = [0; 16];
+
+        ResultCode(unsafe { ctru_sys::FSUSER_GetSdmcCid(cid.as_mut_ptr(), cid.len() as u32) })?;
+        Ok(cid)
+    }
+
+    /// Returns the speed settings of the SD card interface.
+    pub fn get_sdmc_speed_info(&self) -> crate::Result<SdmcSpeedInfo> {
+        let mut info = ctru_sys::FS_SdMmcSpeedInfo::default();
+
+        ResultCode(unsafe { ctru_sys::FSUSER_GetSdmcSpeedInfo(&mut info) })?;
+        Ok(SdmcSpeedInfo {
+            high_speed_mode_enabled: info.highSpeedModeEnabled,
+            uses_highest_clock_rate: info.usesHighestClockRate,
+            sd_clk_ctrl: info.sdClkCtrl,
+        })
+    }
+
+    /// Returns whether a game card is inserted in the card slot.
+    pub fn is_card_inserted(&self) -> crate::Result<bool> {
+        let mut inserted = false;
+
+        ResultCode(unsafe { ctru_sys::FSUSER_CardSlotIsInserted(&mut inserted) })?;
+        Ok(inserted)
+    }
+
+    /// Returns the type of the inserted game card.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no game card is inserted.
+    pub fn get_card_type(&self) -> crate::Result<CardType> {
+        let mut card_type = 0;
+
+        ResultCode(unsafe { ctru_sys::FSUSER_GetCardType(&mut card_type) })?;
+        Ok(card_type.into())
+    }
+
+    /// Returns a handle to the save data of the running application.
+    pub fn save_data(&self) -> crate::Result<SaveArchive> {
+        self.open_save_archive(ArchiveID::Savedata, &ArchivePath::empty())
+    }
+
+    /// Opens the save data archive `id` (e.g. [`ArchiveID::UserSavedata`]), identified by `path`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Fs::open_archive`].
+    pub fn open_save_archive(
+        &self,
+        id: ArchiveID,
+        path: &ArchivePath,
+    ) -> crate::Result<SaveArchive> {
+        let archive = self.open_archive(id, path)?;
+
+        Ok(SaveArchive { archive })
+    }
+
+    /// Creates the extdata `id` on `media`. `smdh` is the icon shown in the Data Management menu
+    /// of the System Settings, usually the SMDH of the application.
+    ///
+    /// The new extdata can then be opened with [`ArchivePath::extdata`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the extdata already exists or if the application
+    /// isn't allowed to create it.
+    pub fn create_extdata(
+        &self,
+        media: MediaType,
+        id: u64,
+        params: &ExtdataParams,
+        smdh: &[u8],
+    ) -> crate::Result<()> {
+        ResultCode(unsafe {
+            ctru_sys::FSUSER_CreateExtSaveData(
+                extdata_info(media, id),
+                params.directories,
+                params.files,
+                params.size_limit,
+                smdh.len() as u32,
+                // The SMDH is only read
+                smdh.as_ptr() as *mut u8,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Deletes the extdata `id` on `media`, with all its content.
+    pub fn delete_extdata(&self, media: MediaType, id: u64) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::FSUSER_DeleteExtSaveData(extdata_info(media, id)) })?;
+        Ok(())
+    }
+
+    /// Returns the IDs of the extdata stored on `media`, or of the shared extdata if `shared` is `true`.
+    pub fn extdata_ids(&self, media: MediaType, shared: bool) -> crate::Result<Vec<u64>> {
+        let mut ids = vec![0u32; 64];
+
+        loop {
+            let mut written = 0;
+            ResultCode(unsafe {
+                ctru_sys::FSUSER_EnumerateExtSaveData(
+                    &mut written,
+                    (ids.len() * mem::size_of::<u32>()) as u32,
+                    media.into(),
+                    mem::size_of::<u32>() as u32,
+                    shared,
+                    ids.as_mut_ptr().cast(),
+                )
+            })?;
+
+            // A full buffer may mean that some IDs didn't fit.
+            if (written as usize) < ids.len() {
+                ids.truncate(written as usize);
+                return Ok(ids.into_iter().map(u64::from).collect());
             }
+            ids.resize(ids.len() * 2, 0);
+        }
+    }
+
+    /// Returns the secure value of the save data of `title`, if it was ever set.
+    ///
+    /// The secure value is stored outside of the save data, so that restoring an older save can be
+    /// detected.
+    pub fn get_secure_value(&self, title: TitleId) -> crate::Result<Option<u64>> {
+        let mut exists = false;
+        let mut value = 0;
+        let (unique_id, variation) = secure_value_title(title);
+
+        ResultCode(unsafe {
+            ctru_sys::FSUSER_GetSaveDataSecureValue(
+                &mut exists,
+                &mut value,
+                ctru_sys::SECUREVALUE_SLOT_SD,
+                unique_id,
+                variation,
+            )
+        })?;
+        Ok(if exists { Some(value) } else { None })
+    }
+
+    /// Sets the secure value of the save data of `title`.
+    pub fn set_secure_value(&self, title: TitleId, value: u64) -> crate::Result<()> {
+        let (unique_id, variation) = secure_value_title(title);
+
+        ResultCode(unsafe {
+            ctru_sys::FSUSER_SetSaveDataSecureValue(
+                value,
+                ctru_sys::SECUREVALUE_SLOT_SD,
+                unique_id,
+                variation,
+            )
+        })?;
+        Ok(())
+    }
+}
+
+impl ArchivePath {
+    /// An empty path, used by the archives that don't need one (SD card, own save data...).
+    pub fn empty() -> Self {
+        ArchivePath {
+            kind: PathType::Empty,
+            data: Vec::new(),
+        }
+    }
+
+    /// An empty binary path, to be filled with the methods below.
+    pub fn binary() -> Self {
+        ArchivePath {
+            kind: PathType::Binary,
+            data: Vec::new(),
+        }
+    }
+
+    /// Path of the extdata `id` stored on `media`, for [`ArchiveID::Extdata`].
+    pub fn extdata(media: MediaType, id: u64) -> Self {
+        Self::binary().media_type(media).u64(id)
+    }
+
+    /// Path of the shared extdata `id` (e.g. `0xF000000B`), for [`ArchiveID::SharedExtdata`].
+    pub fn shared_extdata(id: u32) -> Self {
+        Self::binary()
+            .media_type(MediaType::Nand)
+            .u32(id)
+            .u32(SHARED_EXTDATA_HIGH_ID)
+    }
+
+    /// Path of the save data of `title` stored on `media`, for [`ArchiveID::UserSavedata`].
+    pub fn user_save_data(media: MediaType, title: TitleId) -> Self {
+        Self::binary().media_type(media).title_id(title)
+    }
+
+    /// Path of the system save data `id`, for [`ArchiveID::SystemSavedata`].
+    pub fn system_save_data(id: u32) -> Self {
+        Self::binary().media_type(MediaType::Nand).u32(id)
+    }
+
+    /// Path of the content of `title` stored on `media`, for [`ArchiveID::SaveDataAndContent`].
+    pub fn save_data_and_content(media: MediaType, title: TitleId) -> Self {
+        Self::binary().title_id(title).media_type(media).u32(0)
+    }
+
+    /// Appends a `u32` to the binary path.
+    pub fn u32(mut self, value: u32) -> Self {
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// Appends a `u64` to the binary path.
+    pub fn u64(mut self, value: u64) -> Self {
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// Appends a media type to the binary path, encoded as a `u32`.
+    pub fn media_type(self, media: MediaType) -> Self {
+        self.u32(ctru_sys::FS_MediaType::from(media))
+    }
+
+    /// Appends a title ID to the binary path, encoded as a `u64`.
+    pub fn title_id(self, title: TitleId) -> Self {
+        self.u64(title.0)
+    }
+
+    /// Returns the type of the path.
+    pub fn kind(&self) -> PathType {
+        self.kind
+    }
+
+    /// Returns the raw data of the path.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Builds the libctru path. It borrows the data of `self`, so it must not outlive it.
+    fn as_raw(&self) -> ctru_sys::FS_Path {
+        // Same as `fsMakePath`, which gives empty paths a single NUL byte.
+        let data: &[u8] = match self.kind {
+            PathType::Empty => b"\0",
+            _ => &self.data,
+        };
+
+        ctru_sys::FS_Path {
+            type_: self.kind.into(),
+            size: data.len() as u32,
+            data: data.as_ptr().cast(),
         }
     }
 }
 
 impl Archive {
+    /// Returns the free space of the archive, in bytes.
+    pub fn get_free_bytes(&self) -> crate::Result<u64> {
+        let mut free_bytes = 0;
+
+        ResultCode(unsafe { ctru_sys::FSUSER_GetFreeBytes(&mut free_bytes, self.handle) })?;
+        Ok(free_bytes)
+    }
+
     /// Retrieves an Archive's [`ArchiveID`]
     ///
     /// [`ArchiveID`]: enum.ArchiveID.html
     pub fn get_id(&self) -> ArchiveID {
         self.id
     }
+
+    /// Returns the path the archive was opened with.
+    pub fn path(&self) -> &ArchivePath {
+        &self.path
+    }
+
+    /// Mounts the archive as the device `name`, so that its content can be accessed through
+    /// [`std::fs`] with paths such as `name:/dir/file.txt`.
+    ///
+    /// The device opens its own handle to the archive: it stays mounted until the returned guard
+    /// is dropped, even if `self` is closed first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a device called `name` is already mounted (`sdmc` is
+    /// mounted at startup, and `romfs` by [`RomFS`](crate::romfs::RomFS)) or if the archive
+    /// couldn't be opened again.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `name` contains a NUL byte.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use ctru::services::fs::{Device, Fs};
+    ///
+    /// let fs = Fs::init().unwrap();
+    /// let save = fs.save_data().unwrap();
+    ///
+    /// let mount = save.mount("save").unwrap();
+    /// std::fs::write(mount.root().join("settings.json"), b"{}").unwrap();
+    /// mount.commit().unwrap();
+    /// ```
+    pub fn mount(&self, name: &str) -> crate::Result<MountGuard> {
+        let name = CString::new(name).expect("The mount name must not contain NUL bytes");
+
+        ResultCode(unsafe {
+            ctru_sys::archiveMount(self.id.into(), self.path.as_raw(), name.as_ptr())
+        })?;
+        Ok(MountGuard { name })
+    }
+}
+
+impl MountGuard {
+    /// Writes the changes made to the mounted save data to the storage.
+    ///
+    /// Like [`SaveArchive::commit`], this isn't done automatically when the device is unmounted.
+    pub fn commit(&self) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::archiveCommitSaveData(self.name.as_ptr()) })?;
+        Ok(())
+    }
+}
+
+impl Device for MountGuard {
+    fn name(&self) -> &str {
+        // The name was built from a `&str`.
+        self.name.to_str().unwrap()
+    }
+}
+
+impl Device for SdmcDevice {
+    fn name(&self) -> &str {
+        "sdmc"
+    }
+}
+
+impl SaveArchive {
+    /// Writes the changes made to the save data since the last commit to the storage.
+    pub fn commit(&self) -> crate::Result<()> {
+        ResultCode(unsafe {
+            ctru_sys::FSUSER_ControlArchive(
+                self.archive.handle,
+                ctru_sys::ARCHIVE_ACTION_COMMIT_SAVE_DATA,
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                0,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Returns a guard that commits the save data when it goes out of scope, even on early returns.
+    pub fn transaction(&self) -> CommitGuard<'_> {
+        CommitGuard { save: self }
+    }
+
+    /// Erases the save data and formats it with `params`, then opens it again.
+    ///
+    /// The archive must be closed while it's formatted, so it's consumed by this method.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the save data couldn't be formatted or opened again.
+    /// In both cases, the archive stays closed.
+    pub fn format(self, params: &FormatParams) -> crate::Result<SaveArchive> {
+        let id = self.archive.id;
+        let path = self.archive.path.clone();
+        drop(self);
+
+        ResultCode(unsafe {
+            ctru_sys::FSUSER_FormatSaveData(
+                id.into(),
+                path.as_raw(),
+                params.blocks,
+                params.directories,
+                params.files,
+                params.directory_buckets,
+                params.file_buckets,
+                params.duplicate_data,
+            )
+        })?;
+
+        let mut handle = 0;
+        ResultCode(unsafe { ctru_sys::FSUSER_OpenArchive(&mut handle, id.into(), path.as_raw()) })?;
+
+        Ok(SaveArchive {
+            archive: Archive { id, path, handle },
+        })
+    }
+}
+
+impl Deref for SaveArchive {
+    type Target = Archive;
+
+    fn deref(&self) -> &Archive {
+        &self.archive
+    }
+}
+
+impl CommitGuard<'_> {
+    /// Commits the save data now, returning the error if it fails.
+    pub fn commit(self) -> crate::Result<()> {
+        let save = self.save;
+        mem::forget(self);
+        save.commit()
+    }
 }
 
 impl File {
@@ -406,26 +1121,55 @@ impl File {
     }
 
     /// Queries metadata about the underlying file.
-    pub fn metadata(&self) -> IoResult<Metadata> {
-        // The only metadata we have for files right now is file size.
-        // This is likely to change in the future.
+    ///
+    /// The modification time is only known when the file is looked up by path, see [`metadata`].
+    pub fn metadata(&self) -> IoResult<Metadata<'static>> {
         unsafe {
             let mut size = 0;
             let r = ctru_sys::FSFILE_GetSize(self.handle, &mut size);
             if r < 0 {
-                Err(IoError::new(
+                return Err(IoError::new(
                     IoErrorKind::PermissionDenied,
                     crate::Error::from(r),
-                ))
+                ));
+            }
+
+            let mut attributes = 0;
+            let r = ctru_sys::FSFILE_GetAttributes(self.handle, &mut attributes);
+            if r < 0 {
+                Err(IoError::new(IoErrorKind::Other, crate::Error::from(r)))
             } else {
                 Ok(Metadata {
-                    attributes: 0,
+                    attributes,
                     size,
+                    location: None,
                 })
             }
         }
     }
 
+    /// Changes the attributes of the underlying file. The directory flag is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file is not opened for writing. As read-only files
+    /// can't be opened for writing, this means [`FsAttribute::READ_ONLY`] can be set but not cleared.
+    pub fn set_attributes(&self, attributes: FsAttribute) -> IoResult<()> {
+        let attributes = attributes - FsAttribute::DIRECTORY;
+
+        unsafe {
+            let r = ctru_sys::FSFILE_SetAttributes(self.handle, attributes.bits());
+            if r < 0 {
+                Err(IoError::new(
+                    IoErrorKind::PermissionDenied,
+                    crate::Error::from(r),
+                ))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
     fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
         unsafe {
             let mut n_read = 0;
@@ -470,10 +1214,10 @@ impl File {
     }
 }
 
-impl Metadata {
+impl Metadata<'_> {
     /// Returns whether this metadata is for a directory.
     pub fn is_dir(&self) -> bool {
-        self.attributes == self.attributes | FsAttribute::FS_ATTRIBUTE_DIRECTORY.bits()
+        self.attributes == self.attributes | FsAttribute::DIRECTORY.bits()
     }
 
     /// Returns whether this metadata is for a regular file.
@@ -490,6 +1234,56 @@ impl Metadata {
     pub fn len(&self) -> u64 {
         self.size
     }
+
+    /// Returns the attributes of the file or directory.
+    pub fn attributes(&self) -> FsAttribute {
+        FsAttribute::from_bits_truncate(self.attributes)
+    }
+
+    /// Returns whether the file or directory is hidden.
+    pub fn is_hidden(&self) -> bool {
+        self.attributes().contains(FsAttribute::HIDDEN)
+    }
+
+    /// Returns whether the file or directory is read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.attributes().contains(FsAttribute::READ_ONLY)
+    }
+
+    /// Returns whether the archive flag (set by FAT when the file is modified) is set.
+    pub fn is_archive(&self) -> bool {
+        self.attributes().contains(FsAttribute::ARCHIVE)
+    }
+
+    /// Returns the last modification time of the file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive doesn't keep timestamps (only the SD card
+    /// does), or if the metadata was queried from an open [`File`].
+    pub fn modified(&self) -> IoResult<SystemTime> {
+        self.location
+            .as_ref()
+            .and_then(|(arch, path)| modification_time(arch, path))
+            .ok_or_else(|| {
+                IoError::new(
+                    IoErrorKind::Unsupported,
+                    "modification time is not available for this file",
+                )
+            })
+    }
+
+    /// Returns the creation time of the file.
+    ///
+    /// # Errors
+    ///
+    /// This function always returns an error, as the filesystem service doesn't provide creation times.
+    pub fn created(&self) -> IoResult<SystemTime> {
+        Err(IoError::new(
+            IoErrorKind::Unsupported,
+            "creation time is not available on this platform",
+        ))
+    }
 }
 
 impl OpenOptions {
@@ -628,38 +1422,173 @@ impl OpenOptions {
             (true, true, true) => {
                 FsOpen::FS_OPEN_READ | FsOpen::FS_OPEN_WRITE | FsOpen::FS_OPEN_CREATE
             }
-            _ => FsOpen::empty(), //failure case
+            _ => FsOpen::empty(), //failure case
+        }
+    }
+}
+
+impl<'a> ReadDir<'a> {
+    /// Sets the number of entries read at once from the filesystem. Larger batches need fewer
+    /// requests to the filesystem service, but take more memory (about 0x228 bytes per entry).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `size` is 0.
+    pub fn batch_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "the batch size must not be 0");
+        self.batch_size = size;
+        self
+    }
+
+    /// Reads the next batch of entries. Returns `false` at the end of the directory.
+    fn read_batch(&mut self) -> IoResult<bool> {
+        self.batch
+            .resize(self.batch_size, ctru_sys::FS_DirectoryEntry::default());
+        self.position = 0;
+
+        let mut entries_read = 0;
+        let r = unsafe {
+            ctru_sys::FSDIR_Read(
+                self.handle.0,
+                &mut entries_read,
+                self.batch.len() as u32,
+                self.batch.as_mut_ptr(),
+            )
+        };
+
+        if r < 0 {
+            self.batch.clear();
+            return Err(IoError::new(IoErrorKind::Other, crate::Error::from(r)));
+        }
+        self.batch.truncate(entries_read as usize);
+        Ok(entries_read > 0)
+    }
+}
+
+impl<'a> Iterator for ReadDir<'a> {
+    type Item = IoResult<DirEntry<'a>>;
+
+    fn next(&mut self) -> Option<IoResult<DirEntry<'a>>> {
+        if self.position == self.batch.len() {
+            if self.finished {
+                return None;
+            }
+
+            match self.read_batch() {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.finished = true;
+                    return None;
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        let entry = self.batch[self.position];
+        self.position += 1;
+
+        Some(Ok(DirEntry {
+            entry,
+            root: self.root.clone(),
+            arch: self.arch,
+        }))
+    }
+}
+
+impl<'a> WalkDir<'a> {
+    /// Skips the entries less than `depth` levels below the root. The default is 1, the entries
+    /// of the root itself, which is never yielded.
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Doesn't descend more than `depth` levels below the root. `1` only yields the entries of the root.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Skips the entries for which `predicate` returns `false`. The content of skipped directories
+    /// isn't read.
+    pub fn filter_entry<F>(mut self, predicate: F) -> Self
+    where
+        F: FnMut(&DirEntry<'a>) -> bool + 'a,
+    {
+        self.filter = Some(Box::new(predicate));
+        self
+    }
+}
+
+impl<'a> Iterator for WalkDir<'a> {
+    type Item = IoResult<WalkEntry<'a>>;
+
+    fn next(&mut self) -> Option<IoResult<WalkEntry<'a>>> {
+        if let Some(root) = self.root.take() {
+            if self.max_depth == 0 {
+                return None;
+            }
+            match read_dir(self.arch, root) {
+                Ok(dir) => self.stack.push(dir),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        loop {
+            let depth = self.stack.len();
+            let entry = match self.stack.last_mut()?.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => {
+                    self.stack.pop();
+                    return Some(Err(e));
+                }
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+
+            if let Some(filter) = &mut self.filter {
+                if !filter(&entry) {
+                    continue;
+                }
+            }
+
+            if entry.is_dir() && depth < self.max_depth {
+                match read_dir(self.arch, entry.path()) {
+                    Ok(dir) => self.stack.push(dir),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+
+            if depth >= self.min_depth {
+                return Some(Ok(WalkEntry { entry, depth }));
+            }
         }
     }
 }
 
-impl<'a> Iterator for ReadDir<'a> {
-    type Item = IoResult<DirEntry<'a>>;
+impl<'a> WalkEntry<'a> {
+    /// Returns the number of levels between the root and this entry. The entries of the root have
+    /// a depth of 1.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
 
-    fn next(&mut self) -> Option<IoResult<DirEntry<'a>>> {
-        unsafe {
-            let mut ret = DirEntry {
-                entry: mem::zeroed(),
-                root: self.root.clone(),
-                arch: self.arch,
-            };
-            let mut entries_read = 0;
-            let entry_count = 1;
-            let r = ctru_sys::FSDIR_Read(
-                self.handle.0,
-                &mut entries_read,
-                entry_count,
-                &mut ret.entry,
-            );
+    /// Returns the entry as read from its directory.
+    pub fn into_entry(self) -> DirEntry<'a> {
+        self.entry
+    }
+}
 
-            if r < 0 {
-                return Some(Err(IoError::new(IoErrorKind::Other, crate::Error::from(r))));
-            }
-            if entries_read != entry_count {
-                return None;
-            }
-            Some(Ok(ret))
-        }
+impl<'a> Deref for WalkEntry<'a> {
+    type Target = DirEntry<'a>;
+
+    fn deref(&self) -> &DirEntry<'a> {
+        &self.entry
     }
 }
 
@@ -672,9 +1601,41 @@ impl<'a> DirEntry<'a> {
         self.root.join(self.file_name())
     }
 
+    /// Returns whether this entry is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.attributes().contains(FsAttribute::DIRECTORY)
+    }
+
+    /// Returns the attributes of the entry.
+    pub fn attributes(&self) -> FsAttribute {
+        FsAttribute::from_bits_truncate(self.entry.attributes)
+    }
+
+    /// Returns the size of the file, in bytes. Directories have a size of 0.
+    pub fn file_size(&self) -> u64 {
+        self.entry.fileSize
+    }
+
+    /// Returns the 8.3 short name of the entry, without its extension (e.g. `LONGFI~1`).
+    pub fn short_name(&self) -> String {
+        c_chars_to_string(&self.entry.shortName)
+    }
+
+    /// Returns the extension of the 8.3 short name of the entry (e.g. `TXT`).
+    pub fn short_extension(&self) -> String {
+        c_chars_to_string(&self.entry.shortExt)
+    }
+
     /// Return the metadata for the file that this entry points at.
-    pub fn metadata(&self) -> IoResult<Metadata> {
-        metadata(self.arch, self.path())
+    ///
+    /// The attributes and size come from the entry itself, so the file isn't opened, and the
+    /// modification time is only queried by [`Metadata::modified`].
+    pub fn metadata(&self) -> IoResult<Metadata<'a>> {
+        Ok(Metadata {
+            attributes: self.entry.attributes,
+            size: self.entry.fileSize,
+            location: Some((self.arch, self.path())),
+        })
     }
 
     /// Returns the bare file name of this directory entry without any other leading path
@@ -700,11 +1661,8 @@ pub fn create_dir<P: AsRef<Path>>(arch: &Archive, path: P) -> IoResult<()> {
     unsafe {
         let path = to_utf16(path.as_ref());
         let fs_path = ctru_sys::fsMakePath(PathType::UTF16.into(), path.as_ptr() as _);
-        let r = ctru_sys::FSUSER_CreateDirectory(
-            arch.handle,
-            fs_path,
-            FsAttribute::FS_ATTRIBUTE_DIRECTORY.bits(),
-        );
+        let r =
+            ctru_sys::FSUSER_CreateDirectory(arch.handle, fs_path, FsAttribute::DIRECTORY.bits());
         if r < 0 {
             Err(IoError::new(IoErrorKind::Other, crate::Error::from(r)))
         } else {
@@ -736,17 +1694,46 @@ pub fn create_dir_all<P: AsRef<Path>>(arch: &Archive, path: P) -> IoResult<()> {
 }
 
 /// Given a path, query the file system to get information about a file, directory, etc
-pub fn metadata<P: AsRef<Path>>(arch: &Archive, path: P) -> IoResult<Metadata> {
-    let maybe_file = File::open(arch, path.as_ref());
-    let maybe_dir = read_dir(arch, path.as_ref());
-    match (maybe_file, maybe_dir) {
-        (Ok(file), _) => file.metadata(),
-        (_, Ok(_dir)) => Ok(Metadata {
-            attributes: FsAttribute::FS_ATTRIBUTE_DIRECTORY.bits(),
+pub fn metadata<P: AsRef<Path>>(arch: &Archive, path: P) -> IoResult<Metadata<'_>> {
+    let path = path.as_ref();
+    let maybe_file = File::open(arch, path);
+    let maybe_dir = read_dir(arch, path);
+    let mut metadata = match (maybe_file, maybe_dir) {
+        (Ok(file), _) => file.metadata()?,
+        (_, Ok(_dir)) => Metadata {
+            attributes: FsAttribute::DIRECTORY.bits(),
             size: 0,
-        }),
-        (Err(e), _) => Err(e),
-    }
+            location: None,
+        },
+        (Err(e), _) => return Err(e),
+    };
+
+    metadata.location = Some((arch, path.to_path_buf()));
+    Ok(metadata)
+}
+
+/// Changes the attributes of the file at the provided path. The directory flag is ignored.
+///
+/// The file is opened for writing to change its attributes, so [`FsAttribute::READ_ONLY`] can be set but
+/// not cleared: once set, the file can't be opened for writing anymore.
+///
+/// # Errors
+///
+/// This function will return an error in the following situations, but is not limited to just
+/// these cases:
+///
+/// * `path` doesn't point to an existing file. The attributes of directories can't be changed.
+/// * The user lacks permissions to write to the file, or the file is read-only.
+pub fn set_attributes<P: AsRef<Path>>(
+    arch: &Archive,
+    path: P,
+    attributes: FsAttribute,
+) -> IoResult<()> {
+    let file = OpenOptions::new()
+        .write(true)
+        .archive(arch)
+        .open(path.as_ref())?;
+    file.set_attributes(attributes)
 }
 
 /// Removes an existing, empty directory.
@@ -814,6 +1801,10 @@ pub fn read_dir<P: AsRef<Path>>(arch: &Archive, path: P) -> IoResult<ReadDir> {
                 handle: Dir(handle),
                 root,
                 arch,
+                batch: Vec::new(),
+                batch_size: DEFAULT_BATCH_SIZE,
+                position: 0,
+                finished: false,
             })
         }
     }
@@ -875,11 +1866,319 @@ where
     }
 }
 
+/// Seconds between the UNIX epoch and the epoch of the 3DS timestamps (2000-01-01).
+const TIMESTAMP_EPOCH_OFFSET: u64 = 946_684_800;
+
+/// Returns the modification time of `path`, if the archive keeps timestamps.
+fn modification_time(arch: &Archive, path: &Path) -> Option<SystemTime> {
+    let path = to_utf16(path);
+    let path = path.as_slice_with_nul();
+    let mut timestamp: u64 = 0;
+
+    let r = unsafe {
+        ctru_sys::FSUSER_ControlArchive(
+            arch.handle,
+            ctru_sys::ARCHIVE_ACTION_GET_TIMESTAMP,
+            path.as_ptr() as *mut _,
+            mem::size_of_val(path) as u32,
+            (&mut timestamp as *mut u64).cast(),
+            mem::size_of::<u64>() as u32,
+        )
+    };
+
+    if r < 0 {
+        None
+    } else {
+        // The timestamp is in milliseconds.
+        Some(
+            SystemTime::UNIX_EPOCH
+                + Duration::from_secs(TIMESTAMP_EPOCH_OFFSET)
+                + Duration::from_millis(timestamp),
+        )
+    }
+}
+
+/// Returns an iterator over the entries within a directory and all its subdirectories.
+///
+/// The iterator is configured with [`WalkDir::min_depth`], [`WalkDir::max_depth`] and
+/// [`WalkDir::filter_entry`]. Errors (including a missing `path`) are returned by the iterator,
+/// and the directory that caused them is skipped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ctru::services::fs::{self, Fs};
+///
+/// let fs = Fs::init().unwrap();
+/// let sdmc = fs.sdmc().unwrap();
+///
+/// // Lists the files in `/3ds` and its direct subdirectories, skipping hidden ones
+/// let walker = fs::walk_dir(&sdmc, "/3ds")
+///     .max_depth(2)
+///     .filter_entry(|entry| !entry.metadata().map(|m| m.is_hidden()).unwrap_or(false));
+///
+/// for entry in walker {
+///     let entry = entry.unwrap();
+///     println!("{}{}", "  ".repeat(entry.depth() - 1), entry.path().display());
+/// }
+/// ```
+pub fn walk_dir<P: AsRef<Path>>(arch: &Archive, path: P) -> WalkDir<'_> {
+    WalkDir {
+        arch,
+        root: Some(path.as_ref().to_path_buf()),
+        stack: Vec::new(),
+        min_depth: 1,
+        max_depth: usize::MAX,
+        filter: None,
+    }
+}
+
+/// Copies the content of a file to another one, which may be in another archive, and returns
+/// the number of bytes copied.
+///
+/// The destination file is created if it doesn't exist, and replaced if it does. Remember to
+/// [commit](SaveArchive::commit) save data after writing to it.
+///
+/// # Errors
+///
+/// This function will return an error in the following situations, but is not limited to just
+/// these cases:
+///
+/// * `from` doesn't exist or isn't a file.
+/// * The user lacks permissions to write to `to`.
+/// * There isn't enough free space for the copy.
+pub fn copy<P, Q>(from_arch: &Archive, from: P, to_arch: &Archive, to: Q) -> IoResult<u64>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    copy_with_progress(from_arch, from, to_arch, to, |_| ControlFlow::Continue(()))
+}
+
+/// Same as [`copy`], but calls `progress` before the copy and after each copied chunk.
+///
+/// When `progress` returns [`ControlFlow::Break`], the copy is stopped and an error wrapping
+/// [`Cancelled`] is returned. If the copy had started, the partially written file is removed,
+/// but an existing `to` is left untouched when the copy is stopped before it starts (when
+/// `file_bytes_copied` is 0).
+pub fn copy_with_progress<P, Q, F>(
+    from_arch: &Archive,
+    from: P,
+    to_arch: &Archive,
+    to: Q,
+    mut progress: F,
+) -> IoResult<u64>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(&CopyProgress) -> ControlFlow<()>,
+{
+    let (from, to) = (from.as_ref(), to.as_ref());
+    let size = File::open(from_arch, from)?.metadata()?.len();
+    let mut buffer = vec![0; COPY_BUFFER_SIZE];
+
+    copy_file(
+        (from_arch, from),
+        (to_arch, to),
+        size,
+        &mut buffer,
+        |file_bytes_copied| {
+            progress(&CopyProgress {
+                path: from,
+                file_bytes_copied,
+                file_size: size,
+                total_bytes_copied: file_bytes_copied,
+                total_bytes: size,
+            })
+        },
+    )
+    .map(|()| size)
+}
+
+/// Recursively copies the content of the directory `from` into `to`, which may be in another
+/// archive, and returns the number of bytes copied.
+///
+/// `to` and the missing subdirectories are created, and existing files are replaced.
+/// Remember to [commit](SaveArchive::commit) save data after writing to it.
+///
+/// # Errors
+///
+/// This function will return an error if any directory or file couldn't be read or written.
+/// The files copied before the error are left in place.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ctru::services::fs::{self, Fs};
+///
+/// let fs = Fs::init().unwrap();
+/// let sdmc = fs.sdmc().unwrap();
+/// let save = fs.save_data().unwrap();
+///
+/// // Restores a backup of the save data
+/// fs::copy_dir_all(&sdmc, "/backup", &save, "/").unwrap();
+/// save.commit().unwrap();
+/// ```
+pub fn copy_dir_all<P, Q>(from_arch: &Archive, from: P, to_arch: &Archive, to: Q) -> IoResult<u64>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    copy_dir_all_with_progress(from_arch, from, to_arch, to, |_| ControlFlow::Continue(()))
+}
+
+/// Same as [`copy_dir_all`], but calls `progress` before each file and after each copied chunk.
+///
+/// When `progress` returns [`ControlFlow::Break`], the copy is stopped and an error wrapping
+/// [`Cancelled`] is returned. The files copied before are left in place. If the copy of the
+/// current file had started, the partially written file is removed, but an existing file is left
+/// untouched when the copy is stopped before it starts (when `file_bytes_copied` is 0).
+pub fn copy_dir_all_with_progress<P, Q, F>(
+    from_arch: &Archive,
+    from: P,
+    to_arch: &Archive,
+    to: Q,
+    mut progress: F,
+) -> IoResult<u64>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(&CopyProgress) -> ControlFlow<()>,
+{
+    let (from, to) = (from.as_ref(), to.as_ref());
+
+    // The whole tree is listed first, to know the total size and to avoid copying the copy when
+    // `to` is inside `from`.
+    let mut entries = Vec::new();
+    for entry in walk_dir(from_arch, from) {
+        let entry = entry?;
+        let size = if entry.is_dir() {
+            None
+        } else {
+            Some(entry.file_size())
+        };
+        entries.push((entry.path(), size));
+    }
+    let total_bytes = entries.iter().filter_map(|(_, size)| *size).sum();
+
+    ensure_dir(to_arch, to)?;
+
+    let mut buffer = vec![0; COPY_BUFFER_SIZE];
+    let mut total_bytes_copied = 0;
+
+    for (path, size) in entries {
+        let target = to.join(path.strip_prefix(from).unwrap_or(&path));
+
+        let size = match size {
+            Some(size) => size,
+            None => {
+                ensure_dir(to_arch, &target)?;
+                continue;
+            }
+        };
+
+        copy_file(
+            (from_arch, &path),
+            (to_arch, &target),
+            size,
+            &mut buffer,
+            |file_bytes_copied| {
+                progress(&CopyProgress {
+                    path: &path,
+                    file_bytes_copied,
+                    file_size: size,
+                    total_bytes_copied: total_bytes_copied + file_bytes_copied,
+                    total_bytes,
+                })
+            },
+        )?;
+
+        total_bytes_copied += size;
+    }
+
+    Ok(total_bytes_copied)
+}
+
+/// Size of the chunks read and written by the copy functions.
+const COPY_BUFFER_SIZE: usize = 0x10000;
+
+/// Copies the file `from` of size `size` to `to`, calling `progress` with the number of bytes
+/// copied so far before and after each chunk.
+///
+/// When cancelled before the first chunk, `to` is left untouched. When cancelled later, it is
+/// removed, as its previous content was already replaced.
+fn copy_file<F>(
+    (from_arch, from): (&Archive, &Path),
+    (to_arch, to): (&Archive, &Path),
+    size: u64,
+    buffer: &mut [u8],
+    mut progress: F,
+) -> IoResult<()>
+where
+    F: FnMut(u64) -> ControlFlow<()>,
+{
+    let cancelled = || IoError::new(IoErrorKind::Other, Cancelled);
+
+    if progress(0).is_break() {
+        return Err(cancelled());
+    }
+
+    let mut source = File::open(from_arch, from)?;
+    let mut target = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .archive(to_arch)
+        .open(to)?;
+    // Replaces the previous content and allocates the space at once.
+    target.set_len(size)?;
+
+    let mut copied = 0;
+    while copied < size {
+        let n = source.read(buffer)?;
+        if n == 0 {
+            return Err(IoError::new(
+                IoErrorKind::UnexpectedEof,
+                "the file was shorter than expected",
+            ));
+        }
+        target.write_all(&buffer[..n])?;
+        copied += n as u64;
+
+        if progress(copied).is_break() {
+            // The previous content is already gone, so the partial copy is removed.
+            drop(target);
+            let _ = remove_file(to_arch, to);
+            return Err(cancelled());
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the directory `path` and its parents, unless it already exists.
+fn ensure_dir(arch: &Archive, path: &Path) -> IoResult<()> {
+    create_dir_all(arch, path).or_else(|e| match metadata(arch, path) {
+        Ok(metadata) if metadata.is_dir() => Ok(()),
+        _ => Err(e),
+    })
+}
+
 // TODO: Determine if we should check UTF-16 paths for interior NULs
 fn to_utf16(path: &Path) -> WideCString {
     WideCString::from_str(path).unwrap()
 }
 
+/// Converts a NUL-terminated (or full) ASCII buffer into a string.
+fn c_chars_to_string(chars: &[libc::c_char]) -> String {
+    let bytes = chars
+        .iter()
+        .map(|&c| c as u8)
+        .take_while(|&c| c != 0)
+        .collect::<Vec<u8>>();
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
 // Adapted from sys/windows/fs.rs in libstd
 fn truncate_utf16_at_nul(v: &[u16]) -> &[u16] {
     match v.iter().position(|c| *c == 0) {
@@ -992,6 +2291,14 @@ impl Drop for Archive {
     }
 }
 
+impl Drop for MountGuard {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = ctru_sys::archiveUnmount(self.name.as_ptr());
+        }
+    }
+}
+
 impl Drop for File {
     fn drop(&mut self) {
         unsafe {
@@ -1008,6 +2315,29 @@ impl Drop for Dir {
     }
 }
 
+impl Drop for CommitGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.save.commit();
+    }
+}
+
+/// High half of the IDs of the shared extdata.
+const SHARED_EXTDATA_HIGH_ID: u32 = 0x0004_8000;
+
+fn extdata_info(media: MediaType, id: u64) -> ctru_sys::FS_ExtSaveDataInfo {
+    let mut info = ctru_sys::FS_ExtSaveDataInfo {
+        saveId: id,
+        ..Default::default()
+    };
+    info.set_mediaType(media.into());
+    info
+}
+
+/// Splits a title ID into the unique ID and variation used by the secure value functions.
+fn secure_value_title(title: TitleId) -> (u32, u8) {
+    (((title.0 >> 8) & 0xF_FFFF) as u32, title.0 as u8)
+}
+
 impl From<PathType> for ctru_sys::FS_PathType {
     fn from(p: PathType) -> Self {
         use self::PathType::*;
@@ -1050,3 +2380,439 @@ impl From<ArchiveID> for ctru_sys::FS_ArchiveID {
         }
     }
 }
+
+impl StorageInfo {
+    /// Returns the total space, in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        u64::from(self.total_clusters) * u64::from(self.cluster_size)
+    }
+
+    /// Returns the free space, in bytes.
+    pub fn free_bytes(&self) -> u64 {
+        u64::from(self.free_clusters) * u64::from(self.cluster_size)
+    }
+
+    /// Returns the used space, in bytes.
+    pub fn used_bytes(&self) -> u64 {
+        self.total_bytes().saturating_sub(self.free_bytes())
+    }
+}
+
+impl fmt::Display for StorageInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} free of {}",
+            ByteSize(self.free_bytes()),
+            ByteSize(self.total_bytes())
+        )
+    }
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the copy was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        write!(f, "{value:.1} {}", UNITS[unit])
+    }
+}
+
+impl From<ctru_sys::FS_ArchiveResource> for StorageInfo {
+    fn from(resource: ctru_sys::FS_ArchiveResource) -> Self {
+        StorageInfo {
+            sector_size: resource.sectorSize,
+            cluster_size: resource.clusterSize,
+            total_clusters: resource.totalClusters,
+            free_clusters: resource.freeClusters,
+        }
+    }
+}
+
+impl From<ctru_sys::FS_CardType> for CardType {
+    fn from(card_type: ctru_sys::FS_CardType) -> Self {
+        match card_type {
+            ctru_sys::CARD_CTR => CardType::Ctr,
+            ctru_sys::CARD_TWL => CardType::Twl,
+            value => CardType::Unknown(value),
+        }
+    }
+}
+
+impl From<MediaType> for ctru_sys::FS_MediaType {
+    fn from(m: MediaType) -> Self {
+        use self::MediaType::*;
+        match m {
+            Nand => ctru_sys::MEDIATYPE_NAND,
+            Sd => ctru_sys::MEDIATYPE_SD,
+            GameCard => ctru_sys::MEDIATYPE_GAME_CARD,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test;
+
+    use super::*;
+    use test::Bencher;
+
+    #[test]
+    fn storage_info() {
+        let info = StorageInfo {
+            sector_size: 512,
+            cluster_size: 32 * 1024,
+            total_clusters: 120_000,
+            free_clusters: 40_000,
+        };
+
+        assert_eq!(info.total_bytes(), 3_932_160_000);
+        assert_eq!(info.used_bytes(), 2_621_440_000);
+        assert_eq!(info.to_string(), "1.2 GiB free of 3.7 GiB");
+
+        assert_eq!(ByteSize(0).to_string(), "0 B");
+        assert_eq!(ByteSize(1023).to_string(), "1023 B");
+        assert_eq!(ByteSize(1536).to_string(), "1.5 KiB");
+    }
+
+    #[test]
+    fn archive_paths() {
+        let title = TitleId(0x0004_0000_0005_5D00);
+
+        assert_eq!(ArchivePath::empty().kind(), PathType::Empty);
+        assert!(ArchivePath::empty().as_bytes().is_empty());
+
+        let path = ArchivePath::shared_extdata(0xF000_000B);
+        assert_eq!(path.kind(), PathType::Binary);
+        assert_eq!(
+            path.as_bytes(),
+            [0, 0, 0, 0, 0x0B, 0, 0, 0xF0, 0x00, 0x80, 0x04, 0x00]
+        );
+
+        assert_eq!(
+            ArchivePath::user_save_data(MediaType::Sd, title).as_bytes(),
+            [1, 0, 0, 0, 0x00, 0x5D, 0x05, 0x00, 0x00, 0x00, 0x04, 0x00]
+        );
+
+        assert_eq!(
+            ArchivePath::save_data_and_content(MediaType::GameCard, title).as_bytes(),
+            [0x00, 0x5D, 0x05, 0x00, 0x00, 0x00, 0x04, 0x00, 2, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        assert_eq!(
+            ArchivePath::system_save_data(0x0001_0022).as_bytes(),
+            [0, 0, 0, 0, 0x22, 0x00, 0x01, 0x00]
+        );
+
+        assert_eq!(
+            ArchivePath::extdata(MediaType::Sd, 0x1234),
+            ArchivePath::binary().media_type(MediaType::Sd).u64(0x1234)
+        );
+    }
+
+    #[test]
+    fn read_dir_batches() {
+        let fs = Fs::init().unwrap();
+        let sdmc = fs.sdmc().unwrap();
+        let dir = BenchDir::new(&sdmc, "/ctru-rs-test-read-dir", 10);
+
+        // Batches smaller than, equal to and larger than the directory
+        for batch_size in [3, 10, 64] {
+            let mut names = read_dir(&sdmc, &dir.path)
+                .unwrap()
+                .batch_size(batch_size)
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .collect::<Vec<_>>();
+            names.sort();
+
+            assert_eq!(names, dir.file_names());
+        }
+    }
+
+    #[test]
+    fn modification_times() {
+        let fs = Fs::init().unwrap();
+        let sdmc = fs.sdmc().unwrap();
+        let dir = BenchDir::new(&sdmc, "/ctru-rs-test-modified", 1);
+        let path = format!("{}/{}", dir.path, dir.file_names()[0]);
+
+        let entry = read_dir(&sdmc, &dir.path).unwrap().next().unwrap().unwrap();
+        let from_entry = entry.metadata().unwrap().modified().unwrap();
+        let from_path = metadata(&sdmc, &path).unwrap().modified().unwrap();
+        assert_eq!(from_entry, from_path);
+        assert!(from_path > SystemTime::UNIX_EPOCH);
+
+        // Open files don't know their path, so their modification time can't be queried.
+        let file = File::open(&sdmc, &path).unwrap();
+        assert!(file.metadata().unwrap().modified().is_err());
+    }
+
+    #[test]
+    fn walk_dir_depth_and_filter() {
+        let fs = Fs::init().unwrap();
+        let sdmc = fs.sdmc().unwrap();
+        let dir = TestTree::new(&sdmc, "/ctru-rs-test-walk-dir");
+
+        let walk = |walker: WalkDir| {
+            let mut entries = walker
+                .map(|entry| {
+                    let entry = entry.unwrap();
+                    let path = entry.path();
+                    let path = path.strip_prefix(&dir.root.path).unwrap();
+                    (path.to_str().unwrap().to_owned(), entry.depth())
+                })
+                .collect::<Vec<_>>();
+            entries.sort();
+            entries
+        };
+        let entry = |path: &str, depth| (path.to_owned(), depth);
+
+        assert_eq!(walk(walk_dir(&sdmc, &dir.root.path)).len(), 8);
+        assert_eq!(
+            walk(walk_dir(&sdmc, &dir.root.path).min_depth(2).max_depth(2)),
+            [
+                entry("skip/c.txt", 2),
+                entry("sub/a.txt", 2),
+                entry("sub/deep", 2)
+            ]
+        );
+        assert!(walk(walk_dir(&sdmc, &dir.root.path).max_depth(2))
+            .iter()
+            .all(|(_, depth)| *depth <= 2));
+        assert!(walk(walk_dir(&sdmc, &dir.root.path).max_depth(0)).is_empty());
+
+        // The content of filtered out directories isn't yielded either.
+        let filtered =
+            walk(walk_dir(&sdmc, &dir.root.path).filter_entry(|e| e.file_name() != "skip"));
+        assert_eq!(filtered.len(), 6);
+        assert!(filtered.iter().all(|(path, _)| !path.starts_with("skip")));
+    }
+
+    #[test]
+    fn copy_dir_progress_and_cancellation() {
+        let fs = Fs::init().unwrap();
+        let sdmc = fs.sdmc().unwrap();
+        let source = TestTree::new(&sdmc, "/ctru-rs-test-copy-from");
+        let target = BenchDir::new(&sdmc, "/ctru-rs-test-copy-to", 0);
+
+        let mut last = None;
+        let copied =
+            copy_dir_all_with_progress(&sdmc, &source.root.path, &sdmc, &target.path, |p| {
+                last = Some((p.total_bytes_copied, p.total_bytes));
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+
+        assert_eq!(copied, source.total_bytes());
+        assert_eq!(last, Some((copied, copied)));
+        let mut content = String::new();
+        File::open(&sdmc, format!("{}/sub/deep/b.txt", target.path))
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "b");
+
+        // Cancels once the first file is written: it is removed, as would a partially written one.
+        let _ = remove_dir_all(&sdmc, &target.path);
+        let mut cancelled_path = None;
+        let error =
+            copy_dir_all_with_progress(&sdmc, &source.root.path, &sdmc, &target.path, |p| {
+                if p.file_size > 0 && p.file_bytes_copied == p.file_size {
+                    cancelled_path =
+                        Some(p.path.strip_prefix(&source.root.path).unwrap().to_owned());
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            })
+            .unwrap_err();
+
+        assert_eq!(error.kind(), IoErrorKind::Other);
+        assert!(error.get_ref().unwrap().is::<Cancelled>());
+        let cancelled_path = Path::new(&target.path).join(cancelled_path.unwrap());
+        assert!(metadata(&sdmc, cancelled_path).is_err());
+        assert!(walk_dir(&sdmc, &target.path).all(|entry| entry.unwrap().is_dir()));
+    }
+
+    #[test]
+    fn copy_cancelled_before_start_keeps_destination() {
+        let fs = Fs::init().unwrap();
+        let sdmc = fs.sdmc().unwrap();
+        let source = TestTree::new(&sdmc, "/ctru-rs-test-cancel-from");
+        let target = TestTree::new(&sdmc, "/ctru-rs-test-cancel-to");
+        let from = format!("{}/sub/a.txt", source.root.path);
+        let to = format!("{}/sub/deep/b.txt", target.root.path);
+
+        let read = |path: &str| {
+            let mut content = String::new();
+            File::open(&sdmc, path)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+        let stop_at_start = |p: &CopyProgress| {
+            if p.file_bytes_copied == 0 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        };
+
+        let error = copy_with_progress(&sdmc, &from, &sdmc, &to, stop_at_start).unwrap_err();
+        assert!(error.get_ref().unwrap().is::<Cancelled>());
+        assert_eq!(read(&to), "b");
+
+        let error = copy_dir_all_with_progress(
+            &sdmc,
+            &source.root.path,
+            &sdmc,
+            &target.root.path,
+            stop_at_start,
+        )
+        .unwrap_err();
+        assert!(error.get_ref().unwrap().is::<Cancelled>());
+        assert_eq!(read(&to), "b");
+        assert_eq!(read(&format!("{}/sub/a.txt", target.root.path)), "a");
+    }
+
+    // The benchmarks list a directory with the size of each file, as a file manager would.
+
+    #[bench]
+    fn read_dir_one_by_one(b: &mut Bencher) {
+        let fs = Fs::init().unwrap();
+        let sdmc = fs.sdmc().unwrap();
+        let dir = BenchDir::new(&sdmc, "/ctru-rs-bench-one-by-one", BENCH_FILES);
+
+        // How `ReadDir` and `DirEntry::metadata` used to work: one request per entry, and the
+        // file opened again to get its size.
+        b.iter(|| {
+            let handle = read_dir(&sdmc, &dir.path).unwrap().handle;
+            let mut total = 0;
+
+            loop {
+                let mut entry = ctru_sys::FS_DirectoryEntry::default();
+                let mut entries_read = 0;
+                let r = unsafe { ctru_sys::FSDIR_Read(handle.0, &mut entries_read, 1, &mut entry) };
+                if r < 0 || entries_read != 1 {
+                    break;
+                }
+
+                let name = truncate_utf16_at_nul(&entry.name);
+                let name = String::from_utf16_lossy(name);
+                let file = File::open(&sdmc, Path::new(&dir.path).join(name)).unwrap();
+                total += file.metadata().unwrap().len();
+            }
+
+            total
+        });
+    }
+
+    #[bench]
+    fn read_dir_batched(b: &mut Bencher) {
+        let fs = Fs::init().unwrap();
+        let sdmc = fs.sdmc().unwrap();
+        let dir = BenchDir::new(&sdmc, "/ctru-rs-bench-batched", BENCH_FILES);
+
+        b.iter(|| {
+            read_dir(&sdmc, &dir.path)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_size())
+                .sum::<u64>()
+        });
+    }
+
+    const BENCH_FILES: usize = 200;
+
+    /// Directory filled with small files on the SD card, removed on drop.
+    struct BenchDir<'a> {
+        arch: &'a Archive,
+        path: String,
+        files: usize,
+    }
+
+    impl<'a> BenchDir<'a> {
+        fn new(arch: &'a Archive, path: &str, files: usize) -> Self {
+            let _ = remove_dir_all(arch, path);
+            create_dir(arch, path).unwrap();
+
+            let dir = BenchDir {
+                arch,
+                path: path.to_owned(),
+                files,
+            };
+            for name in dir.file_names() {
+                let mut file = File::create(arch, format!("{path}/{name}")).unwrap();
+                file.write_all(name.as_bytes()).unwrap();
+            }
+            dir
+        }
+
+        fn file_names(&self) -> Vec<String> {
+            (0..self.files)
+                .map(|i| format!("file-{i:04}.txt"))
+                .collect()
+        }
+    }
+
+    impl Drop for BenchDir<'_> {
+        fn drop(&mut self) {
+            let _ = remove_dir_all(self.arch, &self.path);
+        }
+    }
+
+    /// Directory tree on the SD card, removed on drop:
+    ///
+    /// ```text
+    /// file-0000.txt, file-0001.txt
+    /// skip/c.txt
+    /// sub/a.txt
+    /// sub/deep/b.txt
+    /// ```
+    struct TestTree<'a> {
+        root: BenchDir<'a>,
+    }
+
+    impl<'a> TestTree<'a> {
+        fn new(arch: &'a Archive, path: &str) -> Self {
+            let root = BenchDir::new(arch, path, 2);
+
+            for dir in ["skip", "sub", "sub/deep"] {
+                create_dir(arch, format!("{path}/{dir}")).unwrap();
+            }
+            for (file, content) in [
+                ("skip/c.txt", "c"),
+                ("sub/a.txt", "a"),
+                ("sub/deep/b.txt", "b"),
+            ] {
+                let mut file = File::create(arch, format!("{path}/{file}")).unwrap();
+                file.write_all(content.as_bytes()).unwrap();
+            }
+
+            TestTree { root }
+        }
+
+        /// Total size of the files of the tree
+        fn total_bytes(&self) -> u64 {
+            let names: usize = self.root.file_names().iter().map(String::len).sum();
+            names as u64 + 3
+        }
+    }
+}
@@ -0,0 +1,507 @@
+//! Circle pad processing
+//!
+//! The raw values returned by [`CirclePosition::get`] vary between units, never quite reach the same
+//! maximum in every direction, and drift a little around zero. A [`CirclePad`] turns them into a
+//! [`StickVector`] normalised to the unit circle, with configurable dead zones and response curve,
+//! that can also be read as a digital [`Direction`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ctru::prelude::*;
+//! use ctru::services::hid::circle_pad::CirclePad;
+//!
+//! let apt = Apt::init().unwrap();
+//! let hid = Hid::init().unwrap();
+//! let mut circle_pad = CirclePad::default();
+//!
+//! while apt.main_loop() {
+//!     hid.scan_input();
+//!
+//!     let stick = circle_pad.read();
+//!     if let Some(direction) = stick.direction8(0.5) {
+//!         println!("Moving {direction:?} at {:.2}", stick.magnitude());
+//!     }
+//! }
+//! ```
+
+use std::f32::consts::PI;
+
+use super::CirclePosition;
+
+/// Distance from the centre usually reached by the circle pad at its rim.
+const DEFAULT_RANGE: i16 = 156;
+
+/// Range of raw values covered by the circle pad.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Calibration {
+    /// Raw position when the circle pad is at rest.
+    pub center: (i16, i16),
+    /// Smallest raw value reached on each axis.
+    pub min: (i16, i16),
+    /// Biggest raw value reached on each axis.
+    pub max: (i16, i16),
+}
+
+/// Records raw positions to compute a [`Calibration`].
+///
+/// A calibration routine usually asks the user to leave the circle pad alone for a moment
+/// (feeding [`Calibrator::record_center`]), then to roll it around its rim a few times
+/// (feeding [`Calibrator::record_range`]).
+#[derive(Clone, Debug, Default)]
+pub struct Calibrator {
+    center_sum: (i64, i64),
+    center_count: i64,
+    min: Option<(i16, i16)>,
+    max: Option<(i16, i16)>,
+}
+
+/// Shape applied to the distance from the centre, after the dead zones.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResponseCurve {
+    /// The output grows as the circle pad is pushed.
+    Linear,
+    /// Finer control near the centre.
+    Quadratic,
+    /// Even finer control near the centre.
+    Cubic,
+    /// Custom exponent. Values above 1 give finer control near the centre, values below 1 near the rim.
+    Power(f32),
+}
+
+/// Parameters used by a [`CirclePad`] to process raw positions.
+///
+/// Dead zones are expressed as a fraction of the range (between 0 and 1).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CirclePadConfig {
+    pub calibration: Calibration,
+    /// Distance from the centre under which the circle pad is considered at rest.
+    pub radial_dead_zone: f32,
+    /// Distance from each axis under which the position snaps onto it,
+    /// which makes it easier to move in a straight line.
+    pub axial_dead_zone: f32,
+    /// Distance from the rim over which the circle pad is considered fully pushed.
+    pub outer_dead_zone: f32,
+    pub curve: ResponseCurve,
+}
+
+/// Processed circle pad position.
+///
+/// Both components are between -1 and 1, and the vector never leaves the unit circle.
+/// `x` grows to the right and `y` grows upwards.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StickVector {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Digital direction of the circle pad.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+/// Reads and processes the position of the circle pad.
+pub struct CirclePad {
+    config: CirclePadConfig,
+    position: CirclePosition,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            center: (0, 0),
+            min: (-DEFAULT_RANGE, -DEFAULT_RANGE),
+            max: (DEFAULT_RANGE, DEFAULT_RANGE),
+        }
+    }
+}
+
+impl Calibration {
+    /// Maps a raw position to the [-1, 1] range on each axis, without any dead zone.
+    pub fn normalize(&self, (x, y): (i16, i16)) -> (f32, f32) {
+        (
+            normalize_axis(x, self.center.0, self.min.0, self.max.0),
+            normalize_axis(y, self.center.1, self.min.1, self.max.1),
+        )
+    }
+}
+
+fn normalize_axis(value: i16, center: i16, min: i16, max: i16) -> f32 {
+    let offset = f32::from(value) - f32::from(center);
+    let range = if offset >= 0.0 {
+        f32::from(max) - f32::from(center)
+    } else {
+        f32::from(center) - f32::from(min)
+    };
+
+    if range <= 0.0 {
+        0.0
+    } else {
+        (offset / range).clamp(-1.0, 1.0)
+    }
+}
+
+impl Calibrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a position while the circle pad is at rest.
+    pub fn record_center(&mut self, (x, y): (i16, i16)) {
+        self.center_sum.0 += i64::from(x);
+        self.center_sum.1 += i64::from(y);
+        self.center_count += 1;
+    }
+
+    /// Records a position while the circle pad is moved around its rim.
+    pub fn record_range(&mut self, (x, y): (i16, i16)) {
+        self.min = Some(match self.min {
+            Some((min_x, min_y)) => (min_x.min(x), min_y.min(y)),
+            None => (x, y),
+        });
+        self.max = Some(match self.max {
+            Some((max_x, max_y)) => (max_x.max(x), max_y.max(y)),
+            None => (x, y),
+        });
+    }
+
+    /// Computes the calibration out of the recorded positions.
+    ///
+    /// Values that couldn't be measured (for example, if the circle pad was never pushed left)
+    /// fall back to the default range around the measured centre.
+    pub fn finish(&self) -> Calibration {
+        let center = if self.center_count > 0 {
+            (
+                (self.center_sum.0 / self.center_count) as i16,
+                (self.center_sum.1 / self.center_count) as i16,
+            )
+        } else {
+            (0, 0)
+        };
+
+        let (min_x, min_y) = self.min.unwrap_or(center);
+        let (max_x, max_y) = self.max.unwrap_or(center);
+        let lower = |measured: i16, center: i16| {
+            if measured < center {
+                measured
+            } else {
+                center.saturating_sub(DEFAULT_RANGE)
+            }
+        };
+        let upper = |measured: i16, center: i16| {
+            if measured > center {
+                measured
+            } else {
+                center.saturating_add(DEFAULT_RANGE)
+            }
+        };
+
+        Calibration {
+            center,
+            min: (lower(min_x, center.0), lower(min_y, center.1)),
+            max: (upper(max_x, center.0), upper(max_y, center.1)),
+        }
+    }
+}
+
+impl ResponseCurve {
+    /// Applies the curve to a distance between 0 and 1.
+    pub fn apply(&self, value: f32) -> f32 {
+        let value = value.clamp(0.0, 1.0);
+
+        match *self {
+            Self::Linear => value,
+            Self::Quadratic => value * value,
+            Self::Cubic => value * value * value,
+            Self::Power(exponent) => value.powf(exponent),
+        }
+    }
+}
+
+impl Default for CirclePadConfig {
+    fn default() -> Self {
+        Self {
+            calibration: Calibration::default(),
+            radial_dead_zone: 0.15,
+            axial_dead_zone: 0.0,
+            outer_dead_zone: 0.05,
+            curve: ResponseCurve::Linear,
+        }
+    }
+}
+
+impl CirclePadConfig {
+    /// Turns a raw position into a [`StickVector`].
+    pub fn process(&self, raw: (i16, i16)) -> StickVector {
+        let (x, y) = self.calibration.normalize(raw);
+        let x = apply_axial_dead_zone(x, self.axial_dead_zone);
+        let y = apply_axial_dead_zone(y, self.axial_dead_zone);
+
+        let magnitude = x.hypot(y);
+        let inner = self.radial_dead_zone.clamp(0.0, 1.0);
+        let outer = (1.0 - self.outer_dead_zone).clamp(inner, 1.0);
+
+        if magnitude <= inner || magnitude == 0.0 {
+            return StickVector::default();
+        }
+
+        let scaled = if outer > inner {
+            ((magnitude - inner) / (outer - inner)).min(1.0)
+        } else {
+            1.0
+        };
+        let scale = self.curve.apply(scaled) / magnitude;
+
+        StickVector {
+            x: x * scale,
+            y: y * scale,
+        }
+    }
+}
+
+fn apply_axial_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let dead_zone = dead_zone.clamp(0.0, 1.0);
+
+    if value.abs() <= dead_zone {
+        0.0
+    } else if dead_zone >= 1.0 {
+        value.signum()
+    } else {
+        value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+    }
+}
+
+impl StickVector {
+    /// Distance from the centre, between 0 and 1.
+    pub fn magnitude(&self) -> f32 {
+        self.x.hypot(self.y).min(1.0)
+    }
+
+    /// Angle in radians, counter-clockwise from the right.
+    pub fn angle(&self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    /// Returns the closest of the 8 directions, or `None` if the magnitude is under `threshold`.
+    pub fn direction8(&self, threshold: f32) -> Option<Direction> {
+        const DIRECTIONS: [Direction; 8] = [
+            Direction::Right,
+            Direction::UpRight,
+            Direction::Up,
+            Direction::UpLeft,
+            Direction::Left,
+            Direction::DownLeft,
+            Direction::Down,
+            Direction::DownRight,
+        ];
+
+        self.sector(threshold, DIRECTIONS.len())
+            .map(|sector| DIRECTIONS[sector])
+    }
+
+    /// Returns the closest of the 4 cardinal directions, or `None` if the magnitude is under `threshold`.
+    pub fn direction4(&self, threshold: f32) -> Option<Direction> {
+        const DIRECTIONS: [Direction; 4] = [
+            Direction::Right,
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+        ];
+
+        self.sector(threshold, DIRECTIONS.len())
+            .map(|sector| DIRECTIONS[sector])
+    }
+
+    fn sector(&self, threshold: f32, sectors: usize) -> Option<usize> {
+        let magnitude = self.magnitude();
+        if magnitude == 0.0 || magnitude < threshold {
+            return None;
+        }
+
+        let width = 2.0 * PI / sectors as f32;
+        let sector = (self.angle() / width).round() as isize;
+        Some(sector.rem_euclid(sectors as isize) as usize)
+    }
+}
+
+impl Default for CirclePad {
+    fn default() -> Self {
+        Self::new(CirclePadConfig::default())
+    }
+}
+
+impl CirclePad {
+    /// Creates a new reader with the given processing parameters.
+    pub fn new(config: CirclePadConfig) -> Self {
+        Self {
+            config,
+            position: CirclePosition::new(),
+        }
+    }
+
+    pub fn config(&self) -> &CirclePadConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: CirclePadConfig) {
+        self.config = config;
+    }
+
+    /// Replaces the calibration, keeping the other parameters.
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.config.calibration = calibration;
+    }
+
+    /// Returns the raw position of the circle pad.
+    pub fn read_raw(&mut self) -> (i16, i16) {
+        self.position.get()
+    }
+
+    /// Returns the processed position of the circle pad.
+    pub fn read(&mut self) -> StickVector {
+        let raw = self.read_raw();
+        self.config.process(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn normalization() {
+        let calibration = Calibration {
+            center: (4, -2),
+            min: (-146, -152),
+            max: (104, 148),
+        };
+
+        assert_eq!(calibration.normalize((4, -2)), (0.0, 0.0));
+        assert_eq!(calibration.normalize((104, 148)), (1.0, 1.0));
+        assert_eq!(calibration.normalize((-146, -152)), (-1.0, -1.0));
+        assert_eq!(calibration.normalize((54, -77)), (0.5, -0.5));
+        // Values outside of the calibrated range are clamped.
+        assert_eq!(calibration.normalize((300, -300)), (1.0, -1.0));
+    }
+
+    #[test]
+    fn radial_dead_zone() {
+        let config = CirclePadConfig::default();
+
+        assert_eq!(config.process((5, -8)), StickVector::default());
+        assert_eq!(config.process((0, 0)), StickVector::default());
+
+        let full = config.process((156, 0));
+        assert_close(full.x, 1.0);
+        assert_close(full.y, 0.0);
+
+        // A diagonal at the corner of the raw range is brought back to the unit circle.
+        let diagonal = config.process((156, 156));
+        assert_close(diagonal.magnitude(), 1.0);
+        assert_close(diagonal.x, diagonal.y);
+
+        // Halfway between the dead zone and the outer dead zone.
+        let config = CirclePadConfig {
+            calibration: Calibration {
+                center: (0, 0),
+                min: (-100, -100),
+                max: (100, 100),
+            },
+            ..config
+        };
+        let half = config.process((0, 55));
+        assert_close(half.x, 0.0);
+        assert_close(half.y, 0.5);
+    }
+
+    #[test]
+    fn axial_dead_zone() {
+        let config = CirclePadConfig {
+            radial_dead_zone: 0.0,
+            axial_dead_zone: 0.2,
+            outer_dead_zone: 0.0,
+            ..Default::default()
+        };
+
+        let vector = config.process((156, 20));
+        assert_close(vector.x, 1.0);
+        assert_close(vector.y, 0.0);
+
+        let vector = config.process((-78, 0));
+        assert_close(vector.x, -0.375);
+    }
+
+    #[test]
+    fn response_curves() {
+        assert_close(ResponseCurve::Linear.apply(0.5), 0.5);
+        assert_close(ResponseCurve::Quadratic.apply(0.5), 0.25);
+        assert_close(ResponseCurve::Cubic.apply(0.5), 0.125);
+        assert_close(ResponseCurve::Power(0.5).apply(0.25), 0.5);
+        assert_close(ResponseCurve::Quadratic.apply(2.0), 1.0);
+
+        let config = CirclePadConfig {
+            radial_dead_zone: 0.0,
+            outer_dead_zone: 0.0,
+            curve: ResponseCurve::Quadratic,
+            ..Default::default()
+        };
+        assert_close(config.process((-78, 0)).x, -0.25);
+    }
+
+    #[test]
+    fn directions() {
+        let vector = |x, y| StickVector { x, y };
+
+        assert_eq!(vector(0.0, 0.0).direction8(0.0), None);
+        assert_eq!(vector(0.1, 0.0).direction8(0.5), None);
+        assert_eq!(vector(0.0, 1.0).direction8(0.5), Some(Direction::Up));
+        assert_eq!(
+            vector(0.7, -0.7).direction8(0.5),
+            Some(Direction::DownRight)
+        );
+        assert_eq!(vector(-0.9, 0.3).direction8(0.5), Some(Direction::Left));
+        assert_eq!(vector(-0.6, 0.6).direction8(0.5), Some(Direction::UpLeft));
+
+        assert_eq!(vector(-0.6, 0.65).direction4(0.5), Some(Direction::Up));
+        assert_eq!(vector(0.9, -0.4).direction4(0.5), Some(Direction::Right));
+        assert_eq!(vector(0.0, -1.0).direction4(0.5), Some(Direction::Down));
+    }
+
+    #[test]
+    fn calibration_routine() {
+        let mut calibrator = Calibrator::new();
+        for sample in [(3, -1), (5, -3), (4, -2)] {
+            calibrator.record_center(sample);
+        }
+        for sample in [(100, 10), (-140, 0), (0, 150), (20, -149), (-30, 90)] {
+            calibrator.record_range(sample);
+        }
+
+        assert_eq!(
+            calibrator.finish(),
+            Calibration {
+                center: (4, -2),
+                min: (-140, -149),
+                max: (100, 150),
+            }
+        );
+
+        // Nothing was recorded: fall back to the default calibration.
+        assert_eq!(Calibrator::new().finish(), Calibration::default());
+    }
+}
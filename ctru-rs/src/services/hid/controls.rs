@@ -0,0 +1,543 @@
+//! Remappable controls
+//!
+//! A [`ControlMap`] binds the abstract actions of an application (jump, pause, etc.) to [`KeyPad`]
+//! combinations, and resolves the keys pressed on each frame into the active actions. Maps can be saved
+//! to and loaded from a simple text profile, so players can keep their own layout.
+//!
+//! Profiles look like this, with one line per action and alternative bindings separated by commas:
+//!
+//! ```text
+//! # Comments start with '#'
+//! jump = A
+//! dash = B+R, ZR
+//! pause = START
+//! ```
+
+use std::fmt::Write as _;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, Read, Result as IoResult, Write};
+use std::path::Path;
+
+use super::{Hid, KeyPad};
+use crate::services::cfgu::{Cfgu, Language, Region};
+use crate::services::fs::{Archive, File, OpenOptions};
+
+/// An action that can be bound in a [`ControlMap`].
+///
+/// This is usually implemented on a fieldless enum listing everything the player can do.
+pub trait Action: Copy + Eq + 'static {
+    /// Every action, in the order they should appear in profiles.
+    const ALL: &'static [Self];
+
+    /// Name identifying the action in saved profiles.
+    ///
+    /// It must be unique, and can't contain `=`, `#` or line breaks.
+    fn name(&self) -> &'static str;
+
+    /// Bindings used when the player hasn't configured this action.
+    ///
+    /// The console's region and language are given so the defaults can follow local conventions.
+    fn default_bindings(&self, region: Region, language: Language) -> Vec<KeyPad>;
+}
+
+/// Bindings between [`Action`]s and [`KeyPad`] combinations.
+///
+/// Each action can have several alternative bindings, and a binding can combine several keys that have
+/// to be held together. When two active bindings overlap (e.g. `B` and `B+R`), only the most specific one
+/// activates its action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControlMap<A: Action> {
+    bindings: Vec<(A, Vec<KeyPad>)>,
+}
+
+/// Error returned when parsing an invalid control profile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseProfileError {
+    /// Line on which the error was found, starting at 1.
+    pub line: usize,
+    pub kind: ParseProfileErrorKind,
+}
+
+/// The reason a control profile could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseProfileErrorKind {
+    /// The line is not in the `action = bindings` form.
+    InvalidLine,
+    /// The key name is not recognised.
+    UnknownKey(String),
+}
+
+/// Names used for the keys in profiles. Only single keys are listed, so a combination can always be
+/// written down unambiguously.
+const KEY_NAMES: &[(&str, KeyPad)] = &[
+    ("A", KeyPad::KEY_A),
+    ("B", KeyPad::KEY_B),
+    ("X", KeyPad::KEY_X),
+    ("Y", KeyPad::KEY_Y),
+    ("L", KeyPad::KEY_L),
+    ("R", KeyPad::KEY_R),
+    ("ZL", KeyPad::KEY_ZL),
+    ("ZR", KeyPad::KEY_ZR),
+    ("START", KeyPad::KEY_START),
+    ("SELECT", KeyPad::KEY_SELECT),
+    ("DUP", KeyPad::KEY_DUP),
+    ("DDOWN", KeyPad::KEY_DDOWN),
+    ("DLEFT", KeyPad::KEY_DLEFT),
+    ("DRIGHT", KeyPad::KEY_DRIGHT),
+    ("CPAD_UP", KeyPad::KEY_CPAD_UP),
+    ("CPAD_DOWN", KeyPad::KEY_CPAD_DOWN),
+    ("CPAD_LEFT", KeyPad::KEY_CPAD_LEFT),
+    ("CPAD_RIGHT", KeyPad::KEY_CPAD_RIGHT),
+    ("CSTICK_UP", KeyPad::KEY_CSTICK_UP),
+    ("CSTICK_DOWN", KeyPad::KEY_CSTICK_DOWN),
+    ("CSTICK_LEFT", KeyPad::KEY_CSTICK_LEFT),
+    ("CSTICK_RIGHT", KeyPad::KEY_CSTICK_RIGHT),
+    ("TOUCH", KeyPad::KEY_TOUCH),
+];
+
+/// Pairs of keys swapped by [`mirror_keys`].
+const MIRRORED_KEYS: &[(KeyPad, KeyPad)] = &[
+    (KeyPad::KEY_DUP, KeyPad::KEY_X),
+    (KeyPad::KEY_DDOWN, KeyPad::KEY_B),
+    (KeyPad::KEY_DLEFT, KeyPad::KEY_Y),
+    (KeyPad::KEY_DRIGHT, KeyPad::KEY_A),
+    (KeyPad::KEY_CPAD_UP, KeyPad::KEY_CSTICK_UP),
+    (KeyPad::KEY_CPAD_DOWN, KeyPad::KEY_CSTICK_DOWN),
+    (KeyPad::KEY_CPAD_LEFT, KeyPad::KEY_CSTICK_LEFT),
+    (KeyPad::KEY_CPAD_RIGHT, KeyPad::KEY_CSTICK_RIGHT),
+    (KeyPad::KEY_L, KeyPad::KEY_R),
+    (KeyPad::KEY_ZL, KeyPad::KEY_ZR),
+    (KeyPad::KEY_SELECT, KeyPad::KEY_START),
+];
+
+/// Swaps the left and right sides of the console: the D-pad with the face buttons, the circle pad with the
+/// C-stick, L with R, ZL with ZR and SELECT with START.
+///
+/// Directions are kept, so `DUP` becomes `X` and `DRIGHT` becomes `A`.
+pub fn mirror_keys(keys: KeyPad) -> KeyPad {
+    let mut mirrored = keys;
+
+    for &(left, right) in MIRRORED_KEYS {
+        mirrored.set(left, keys.contains(right));
+        mirrored.set(right, keys.contains(left));
+    }
+
+    mirrored
+}
+
+impl<A: Action> ControlMap<A> {
+    /// Creates a map where no action is bound.
+    pub fn new() -> Self {
+        Self {
+            bindings: A::ALL.iter().map(|&action| (action, Vec::new())).collect(),
+        }
+    }
+
+    /// Creates the default map for the given region and language.
+    pub fn defaults(region: Region, language: Language) -> Self {
+        Self {
+            bindings: A::ALL
+                .iter()
+                .map(|&action| (action, action.default_bindings(region, language)))
+                .collect(),
+        }
+    }
+
+    /// Creates the default map for the region and language of the console.
+    pub fn system_defaults(cfgu: &Cfgu) -> crate::Result<Self> {
+        Ok(Self::defaults(cfgu.get_region()?, cfgu.get_language()?))
+    }
+
+    /// Returns the bindings of an action.
+    pub fn bindings(&self, action: A) -> &[KeyPad] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Replaces all the bindings of an action.
+    pub fn set_bindings(&mut self, action: A, bindings: Vec<KeyPad>) {
+        match self.bindings.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, keys)) => *keys = bindings,
+            None => self.bindings.push((action, bindings)),
+        }
+    }
+
+    /// Adds an alternative binding to an action. All the keys in `keys` have to be held to activate it.
+    pub fn bind(&mut self, action: A, keys: KeyPad) {
+        if keys.is_empty() {
+            return;
+        }
+
+        match self.bindings.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, bindings)) if !bindings.contains(&keys) => bindings.push(keys),
+            Some(_) => {}
+            None => self.bindings.push((action, vec![keys])),
+        }
+    }
+
+    /// Removes all the bindings of an action.
+    pub fn unbind(&mut self, action: A) {
+        self.set_bindings(action, Vec::new());
+    }
+
+    /// Returns the actions bound to a combination of keys.
+    pub fn actions_for(&self, keys: KeyPad) -> impl Iterator<Item = A> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, bindings)| bindings.contains(&keys))
+            .map(|(action, _)| *action)
+    }
+
+    /// Returns a copy of this map with the left and right sides of the console swapped.
+    ///
+    /// See [`mirror_keys`].
+    pub fn mirrored(&self) -> Self {
+        Self {
+            bindings: self
+                .bindings
+                .iter()
+                .map(|(action, keys)| (*action, keys.iter().copied().map(mirror_keys).collect()))
+                .collect(),
+        }
+    }
+
+    /// Returns the actions active with the given held keys.
+    pub fn resolve(&self, held: KeyPad) -> Vec<A> {
+        self.resolve_with(held, |_| true)
+    }
+
+    /// Returns the actions that became active on this frame, i.e. whose binding is held and
+    /// includes a key that was just pressed.
+    pub fn resolve_pressed(&self, down: KeyPad, held: KeyPad) -> Vec<A> {
+        let held = held | down;
+        self.resolve_with(held, |binding| binding.intersects(down))
+    }
+
+    /// Resolves the active and newly pressed actions from the current state of the [`Hid`] service.
+    ///
+    /// [`Hid::scan_input`] must have been called beforehand on the current frame.
+    pub fn poll(&self, hid: &Hid) -> ResolvedActions<A> {
+        let held = hid.keys_held();
+        let down = hid.keys_down();
+
+        ResolvedActions {
+            held: self.resolve(held),
+            pressed: self.resolve_pressed(down, held),
+        }
+    }
+
+    fn resolve_with(&self, held: KeyPad, filter: impl Fn(KeyPad) -> bool) -> Vec<A> {
+        let active = || {
+            self.bindings
+                .iter()
+                .flat_map(|(action, bindings)| bindings.iter().map(move |keys| (*action, *keys)))
+                .filter(|(_, keys)| !keys.is_empty() && held.contains(*keys))
+        };
+
+        let mut actions = Vec::new();
+        for (action, keys) in active() {
+            // A more specific combination takes priority (e.g. holding B+R shouldn't trigger B's action).
+            let shadowed = active().any(|(_, other)| other != keys && other.contains(keys));
+
+            if !shadowed && filter(keys) && !actions.contains(&action) {
+                actions.push(action);
+            }
+        }
+
+        actions
+    }
+
+    /// Writes the map as a text profile.
+    pub fn to_profile_string(&self) -> String {
+        let mut profile = String::new();
+
+        for (action, bindings) in &self.bindings {
+            let bindings: Vec<String> = bindings.iter().copied().map(format_keys).collect();
+            // Writing to a String can't fail.
+            let _ = writeln!(profile, "{} = {}", action.name(), bindings.join(", "));
+        }
+
+        profile
+    }
+
+    /// Parses a text profile.
+    ///
+    /// Actions missing from the profile are left unbound, while unknown actions are ignored so that profiles
+    /// saved by other versions of the application can still be loaded.
+    pub fn from_profile_str(profile: &str) -> Result<Self, ParseProfileError> {
+        let mut map = Self::new();
+
+        for (index, line) in profile.lines().enumerate() {
+            let error = |kind| ParseProfileError {
+                line: index + 1,
+                kind,
+            };
+
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, bindings) = line
+                .split_once('=')
+                .ok_or_else(|| error(ParseProfileErrorKind::InvalidLine))?;
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(error(ParseProfileErrorKind::InvalidLine));
+            }
+
+            let bindings = bindings
+                .split(',')
+                .map(str::trim)
+                .filter(|binding| !binding.is_empty())
+                .map(|binding| parse_keys(binding).map_err(error))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if let Some(&action) = A::ALL.iter().find(|action| action.name() == name) {
+                map.set_bindings(action, bindings);
+            }
+        }
+
+        Ok(map)
+    }
+
+    /// Saves the map as a text profile at the given path, replacing any previous file.
+    pub fn save<P: AsRef<Path>>(&self, arch: &Archive, path: P) -> IoResult<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .archive(arch)
+            .open(path)?;
+
+        file.write_all(self.to_profile_string().as_bytes())
+    }
+
+    /// Loads a map from the text profile at the given path.
+    ///
+    /// # Errors
+    ///
+    /// Besides I/O errors, this function returns an error of kind [`InvalidData`](IoErrorKind::InvalidData)
+    /// if the profile can't be parsed.
+    pub fn load<P: AsRef<Path>>(arch: &Archive, path: P) -> IoResult<Self> {
+        let mut profile = String::new();
+        File::open(arch, path)?.read_to_string(&mut profile)?;
+
+        Self::from_profile_str(&profile).map_err(|e| IoError::new(IoErrorKind::InvalidData, e))
+    }
+}
+
+impl<A: Action> Default for ControlMap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Actions resolved by [`ControlMap::poll`] for a single frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedActions<A> {
+    /// Actions whose binding is currently held.
+    pub held: Vec<A>,
+    /// Actions that became active on this frame.
+    pub pressed: Vec<A>,
+}
+
+impl<A: PartialEq> ResolvedActions<A> {
+    /// Returns whether the action is held on this frame.
+    pub fn is_held(&self, action: A) -> bool {
+        self.held.contains(&action)
+    }
+
+    /// Returns whether the action was pressed on this frame.
+    pub fn is_pressed(&self, action: A) -> bool {
+        self.pressed.contains(&action)
+    }
+}
+
+fn format_keys(keys: KeyPad) -> String {
+    KEY_NAMES
+        .iter()
+        .filter(|(_, key)| keys.contains(*key))
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn parse_keys(binding: &str) -> Result<KeyPad, ParseProfileErrorKind> {
+    binding
+        .split('+')
+        .map(str::trim)
+        .try_fold(KeyPad::empty(), |keys, name| {
+            KEY_NAMES
+                .iter()
+                .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+                .map(|(_, key)| keys | *key)
+                .ok_or_else(|| ParseProfileErrorKind::UnknownKey(name.to_owned()))
+        })
+}
+
+impl std::fmt::Display for ParseProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.kind {
+            ParseProfileErrorKind::InvalidLine => {
+                write!(f, "line {}: expected `action = bindings`", self.line)
+            }
+            ParseProfileErrorKind::UnknownKey(key) => {
+                write!(f, "line {}: unknown key `{key}`", self.line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseProfileError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum Game {
+        Confirm,
+        Cancel,
+        Run,
+        Dash,
+        Pause,
+    }
+
+    impl Action for Game {
+        const ALL: &'static [Self] = &[
+            Self::Confirm,
+            Self::Cancel,
+            Self::Run,
+            Self::Dash,
+            Self::Pause,
+        ];
+
+        fn name(&self) -> &'static str {
+            match self {
+                Self::Confirm => "confirm",
+                Self::Cancel => "cancel",
+                Self::Run => "run",
+                Self::Dash => "dash",
+                Self::Pause => "pause",
+            }
+        }
+
+        fn default_bindings(&self, region: Region, _language: Language) -> Vec<KeyPad> {
+            let swap_confirm = matches!(region, Region::USA | Region::Europe);
+
+            match self {
+                Self::Confirm if swap_confirm => vec![KeyPad::KEY_B],
+                Self::Confirm => vec![KeyPad::KEY_A],
+                Self::Cancel if swap_confirm => vec![KeyPad::KEY_A],
+                Self::Cancel => vec![KeyPad::KEY_B],
+                Self::Run => vec![KeyPad::KEY_Y],
+                Self::Dash => vec![KeyPad::KEY_Y | KeyPad::KEY_R, KeyPad::KEY_ZR],
+                Self::Pause => vec![KeyPad::KEY_START],
+            }
+        }
+    }
+
+    #[test]
+    fn region_defaults() {
+        let japan = ControlMap::<Game>::defaults(Region::Japan, Language::Japanese);
+        let usa = ControlMap::<Game>::defaults(Region::USA, Language::English);
+
+        assert_eq!(japan.bindings(Game::Confirm), [KeyPad::KEY_A]);
+        assert_eq!(usa.bindings(Game::Confirm), [KeyPad::KEY_B]);
+        assert_eq!(
+            usa.actions_for(KeyPad::KEY_START).collect::<Vec<_>>(),
+            [Game::Pause]
+        );
+    }
+
+    #[test]
+    fn combinations_take_priority() {
+        let map = ControlMap::<Game>::defaults(Region::Japan, Language::Japanese);
+
+        assert_eq!(map.resolve(KeyPad::KEY_Y), [Game::Run]);
+        assert_eq!(map.resolve(KeyPad::KEY_Y | KeyPad::KEY_R), [Game::Dash]);
+        assert_eq!(
+            map.resolve(KeyPad::KEY_A | KeyPad::KEY_Y | KeyPad::KEY_R),
+            [Game::Confirm, Game::Dash]
+        );
+        assert_eq!(
+            map.resolve(KeyPad::KEY_ZR | KeyPad::KEY_Y),
+            [Game::Run, Game::Dash]
+        );
+        assert!(map.resolve(KeyPad::empty()).is_empty());
+    }
+
+    #[test]
+    fn pressed_actions() {
+        let map = ControlMap::<Game>::defaults(Region::Japan, Language::Japanese);
+
+        // R was just pressed while Y was already held.
+        assert_eq!(
+            map.resolve_pressed(KeyPad::KEY_R, KeyPad::KEY_Y | KeyPad::KEY_R),
+            [Game::Dash]
+        );
+        // Nothing new was pressed.
+        assert!(map
+            .resolve_pressed(KeyPad::empty(), KeyPad::KEY_Y | KeyPad::KEY_R)
+            .is_empty());
+    }
+
+    #[test]
+    fn left_handed_mirroring() {
+        assert_eq!(mirror_keys(KeyPad::KEY_A), KeyPad::KEY_DRIGHT);
+        assert_eq!(
+            mirror_keys(KeyPad::KEY_DUP | KeyPad::KEY_L),
+            KeyPad::KEY_X | KeyPad::KEY_R
+        );
+        assert_eq!(mirror_keys(KeyPad::KEY_TOUCH), KeyPad::KEY_TOUCH);
+
+        let map = ControlMap::<Game>::defaults(Region::Japan, Language::Japanese).mirrored();
+        assert_eq!(map.bindings(Game::Confirm), [KeyPad::KEY_DRIGHT]);
+        assert_eq!(
+            map.bindings(Game::Dash),
+            [KeyPad::KEY_DLEFT | KeyPad::KEY_L, KeyPad::KEY_ZL]
+        );
+        assert_eq!(map.mirrored().bindings(Game::Confirm), [KeyPad::KEY_A]);
+    }
+
+    #[test]
+    fn profile_round_trip() {
+        let mut map = ControlMap::<Game>::defaults(Region::Europe, Language::French);
+        map.unbind(Game::Run);
+        map.bind(Game::Pause, KeyPad::KEY_SELECT);
+
+        let profile = map.to_profile_string();
+        assert!(profile.contains("dash = Y+R, ZR\n"));
+        assert!(profile.contains("run = \n"));
+
+        assert_eq!(ControlMap::from_profile_str(&profile), Ok(map));
+    }
+
+    #[test]
+    fn profile_parsing() {
+        let profile = "# comment\n\nconfirm = a   # trailing comment\nunknown = X\ndash=b + r\n";
+        let map = ControlMap::<Game>::from_profile_str(profile).unwrap();
+
+        assert_eq!(map.bindings(Game::Confirm), [KeyPad::KEY_A]);
+        assert_eq!(map.bindings(Game::Dash), [KeyPad::KEY_B | KeyPad::KEY_R]);
+        assert!(map.bindings(Game::Pause).is_empty());
+
+        assert_eq!(
+            ControlMap::<Game>::from_profile_str("confirm = A\npause START"),
+            Err(ParseProfileError {
+                line: 2,
+                kind: ParseProfileErrorKind::InvalidLine
+            })
+        );
+        assert_eq!(
+            ControlMap::<Game>::from_profile_str("confirm = A+HOME"),
+            Err(ParseProfileError {
+                line: 1,
+                kind: ParseProfileErrorKind::UnknownKey("HOME".to_owned())
+            })
+        );
+    }
+}
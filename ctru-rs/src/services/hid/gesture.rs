@@ -0,0 +1,752 @@
+//! Touchscreen gesture recognition
+//!
+//! [`TouchPosition`] only reports where the stylus is on the current frame. A [`GestureRecognizer`]
+//! follows the touch across frames and turns it into higher level [`Gesture`]s: taps, double taps,
+//! long presses, drags and swipes. Gestures can also be hit-tested against rectangles registered
+//! with [`GestureRecognizer::add_region`].
+//!
+//! All timings are counted in frames (i.e. calls to [`GestureRecognizer::update`]), which keeps the
+//! recognizer independent from the system clock and makes it possible to feed it synthetic touch traces.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ctru::prelude::*;
+//! use ctru::services::hid::gesture::{Gesture, GestureRecognizer};
+//! use ctru::services::hid::TouchPosition;
+//!
+//! let apt = Apt::init().unwrap();
+//! let hid = Hid::init().unwrap();
+//! let mut touch = TouchPosition::new();
+//! let mut gestures = GestureRecognizer::default();
+//!
+//! while apt.main_loop() {
+//!     hid.scan_input();
+//!
+//!     for gesture in gestures.poll(&hid, &mut touch) {
+//!         if let Gesture::Tap { position, .. } = gesture {
+//!             println!("Tapped at {}, {}", position.x, position.y);
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::VecDeque;
+
+use super::{Hid, KeyPad, TouchPosition};
+
+/// A position on the touchscreen, in pixels.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Point {
+    pub x: u16,
+    pub y: u16,
+}
+
+/// A rectangle on the touchscreen, used for hit-testing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// Identifier of a rectangle registered with [`GestureRecognizer::add_region`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RegionId(usize);
+
+/// Speed of the stylus, in pixels per frame.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Velocity {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Main direction of a [`Gesture::Swipe`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// A gesture recognized by the [`GestureRecognizer`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Gesture {
+    /// The stylus was pressed and released quickly without moving.
+    Tap {
+        position: Point,
+        region: Option<RegionId>,
+    },
+    /// A second tap happened close (both in time and space) to a previous one.
+    ///
+    /// The first tap of the pair is still reported as a [`Gesture::Tap`].
+    DoubleTap {
+        position: Point,
+        region: Option<RegionId>,
+    },
+    /// The stylus was held still for a while. Reported once, while the stylus is still down.
+    LongPress {
+        position: Point,
+        region: Option<RegionId>,
+    },
+    /// The stylus moved far enough from where it was pressed to start a drag.
+    DragStart {
+        origin: Point,
+        region: Option<RegionId>,
+    },
+    /// The stylus moved during a drag.
+    Drag {
+        position: Point,
+        /// Movement since the previous frame.
+        delta: (i32, i32),
+        velocity: Velocity,
+    },
+    /// The stylus was lifted at the end of a drag.
+    DragEnd {
+        position: Point,
+        /// Region under the position where the stylus was lifted.
+        region: Option<RegionId>,
+        velocity: Velocity,
+    },
+    /// A drag ended with a fast movement in one direction. Reported right after [`Gesture::DragEnd`].
+    Swipe {
+        direction: SwipeDirection,
+        velocity: Velocity,
+    },
+}
+
+/// Thresholds used by the [`GestureRecognizer`].
+///
+/// Times are counted in frames and distances in pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GestureConfig {
+    /// Number of touch samples averaged together to smooth out the panel's jitter.
+    pub smoothing_window: usize,
+    /// Samples further than this from where the stylus was heading are considered noise, unless the next sample
+    /// confirms them.
+    pub max_jump: f32,
+    /// Number of consecutive frames without touch needed before the stylus is considered lifted.
+    pub release_frames: u32,
+    /// Maximum movement allowed for a press to still count as a tap or a long press.
+    pub tap_slop: f32,
+    /// Maximum duration of a tap.
+    pub tap_max_frames: u32,
+    /// Maximum time between two taps for them to count as a double tap.
+    pub double_tap_frames: u32,
+    /// Maximum distance between two taps for them to count as a double tap.
+    pub double_tap_slop: f32,
+    /// Time the stylus has to be held still to trigger a long press.
+    pub long_press_frames: u32,
+    /// Number of frames used to compute the drag velocity.
+    pub velocity_window: usize,
+    /// Minimum length of a drag for it to count as a swipe.
+    pub swipe_min_distance: f32,
+    /// Minimum velocity at the end of a drag for it to count as a swipe.
+    pub swipe_min_velocity: f32,
+}
+
+/// Recognizes gestures out of the touch samples fed to it each frame.
+///
+/// Use [`GestureRecognizer::poll`] to read the touchscreen directly,
+/// or [`GestureRecognizer::update`] to feed it samples from another source.
+#[derive(Clone, Debug)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    regions: Vec<(RegionId, Rect)>,
+    next_region: usize,
+
+    frame: u64,
+    state: State,
+    /// Last denoised position of the stylus.
+    position: Point,
+    last_touch_frame: u64,
+    absent_frames: u32,
+    last_tap: Option<(Point, u64)>,
+
+    /// Latest accepted raw samples, averaged to get the denoised position.
+    samples: VecDeque<Point>,
+    /// Movement between the last two accepted samples, used to predict the next one.
+    step: (i32, i32),
+    /// A sample that jumped too far, waiting to be confirmed by the next one.
+    pending: Option<Point>,
+    /// Latest denoised positions, with the frame they were recorded at.
+    history: VecDeque<(u64, Point)>,
+
+    events: Vec<Gesture>,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum State {
+    Idle,
+    Pressed {
+        origin: Point,
+        start_frame: u64,
+        long_pressed: bool,
+    },
+    Dragging {
+        origin: Point,
+    },
+}
+
+impl Point {
+    pub fn new(x: u16, y: u16) -> Self {
+        Self { x, y }
+    }
+
+    fn offset_to(self, other: Point) -> (i32, i32) {
+        (
+            i32::from(other.x) - i32::from(self.x),
+            i32::from(other.y) - i32::from(self.y),
+        )
+    }
+
+    fn distance(self, other: Point) -> f32 {
+        let (dx, dy) = self.offset_to(other);
+        (dx as f32).hypot(dy as f32)
+    }
+}
+
+impl From<(u16, u16)> for Point {
+    fn from((x, y): (u16, u16)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl Rect {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns whether the point lies inside the rectangle.
+    pub fn contains(&self, point: Point) -> bool {
+        let (x, y) = (u32::from(point.x), u32::from(point.y));
+        let (left, top) = (u32::from(self.x), u32::from(self.y));
+
+        x >= left
+            && y >= top
+            && x < left + u32::from(self.width)
+            && y < top + u32::from(self.height)
+    }
+}
+
+impl Velocity {
+    /// Returns the speed, in pixels per frame.
+    pub fn magnitude(&self) -> f32 {
+        self.x.hypot(self.y)
+    }
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        // Tuned for the usual 60 frames per second main loop.
+        Self {
+            smoothing_window: 3,
+            max_jump: 40.0,
+            release_frames: 2,
+            tap_slop: 8.0,
+            tap_max_frames: 20,
+            double_tap_frames: 18,
+            double_tap_slop: 16.0,
+            long_press_frames: 45,
+            velocity_window: 4,
+            swipe_min_distance: 40.0,
+            swipe_min_velocity: 4.0,
+        }
+    }
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new(GestureConfig::default())
+    }
+}
+
+impl GestureRecognizer {
+    /// Creates a new recognizer with the given thresholds.
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            regions: Vec::new(),
+            next_region: 0,
+            frame: 0,
+            state: State::Idle,
+            position: Point::default(),
+            last_touch_frame: 0,
+            absent_frames: 0,
+            last_tap: None,
+            samples: VecDeque::new(),
+            step: (0, 0),
+            pending: None,
+            history: VecDeque::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the thresholds currently in use.
+    pub fn config(&self) -> &GestureConfig {
+        &self.config
+    }
+
+    /// Changes the thresholds. The gesture in progress (if any) is not interrupted.
+    pub fn set_config(&mut self, config: GestureConfig) {
+        self.config = config;
+    }
+
+    /// Registers a rectangle to be hit-tested against.
+    ///
+    /// When regions overlap, the most recently added one wins.
+    pub fn add_region(&mut self, rect: Rect) -> RegionId {
+        let id = RegionId(self.next_region);
+        self.next_region += 1;
+        self.regions.push((id, rect));
+        id
+    }
+
+    /// Unregisters a rectangle. Returns `false` if the region did not exist.
+    pub fn remove_region(&mut self, id: RegionId) -> bool {
+        let len = self.regions.len();
+        self.regions.retain(|(region, _)| *region != id);
+        self.regions.len() != len
+    }
+
+    /// Unregisters all rectangles.
+    pub fn clear_regions(&mut self) {
+        self.regions.clear();
+    }
+
+    /// Returns the topmost registered region containing the point.
+    pub fn hit_test(&self, point: Point) -> Option<RegionId> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|(_, rect)| rect.contains(point))
+            .map(|(id, _)| *id)
+    }
+
+    /// Returns whether the stylus is currently considered down.
+    pub fn is_touching(&self) -> bool {
+        !matches!(self.state, State::Idle)
+    }
+
+    /// Reads the touchscreen and returns the gestures recognized on this frame.
+    ///
+    /// [`Hid::scan_input`] must have been called beforehand on the current frame.
+    pub fn poll(&mut self, hid: &Hid, touch: &mut TouchPosition) -> &[Gesture] {
+        let sample = if hid.keys_held().contains(KeyPad::KEY_TOUCH) {
+            Some(touch.get())
+        } else {
+            None
+        };
+
+        self.update(sample)
+    }
+
+    /// Feeds the touch sample for the current frame (`None` when the stylus is up)
+    /// and returns the gestures recognized on this frame.
+    ///
+    /// This should be called exactly once per frame.
+    pub fn update(&mut self, sample: Option<(u16, u16)>) -> &[Gesture] {
+        self.events.clear();
+        self.frame += 1;
+
+        // The panel reports (0, 0) when the stylus is being lifted.
+        match sample.map(Point::from).filter(|p| *p != Point::default()) {
+            Some(raw) => {
+                self.absent_frames = 0;
+                if let Some(position) = self.denoise(raw) {
+                    self.on_touch(position);
+                }
+            }
+            None => {
+                self.absent_frames = self.absent_frames.saturating_add(1);
+                if self.is_touching() && self.absent_frames >= self.config.release_frames {
+                    self.on_release();
+                }
+            }
+        }
+
+        &self.events
+    }
+
+    fn denoise(&mut self, raw: Point) -> Option<Point> {
+        if let Some(&previous) = self.samples.back() {
+            // A fast stroke moves a lot between frames, so the sample is compared with where the
+            // stylus would be if it kept moving the same way.
+            if deviation(previous, self.step, raw) > self.config.max_jump {
+                match self.pending.take() {
+                    // The sample continues the jump, or stays where it landed, so the stylus
+                    // really did move.
+                    Some(pending)
+                        if deviation(pending, previous.offset_to(pending), raw)
+                            <= self.config.max_jump
+                            || pending.distance(raw) <= self.config.max_jump =>
+                    {
+                        self.accept(pending);
+                    }
+                    _ => {
+                        self.pending = Some(raw);
+                        return None;
+                    }
+                }
+            }
+        }
+
+        self.pending = None;
+        self.accept(raw);
+
+        let count = self.samples.len() as u32;
+        let (x, y) = self
+            .samples
+            .iter()
+            .fold((0, 0), |(x, y), p| (x + u32::from(p.x), y + u32::from(p.y)));
+
+        Some(Point::new(
+            ((x + count / 2) / count) as u16,
+            ((y + count / 2) / count) as u16,
+        ))
+    }
+
+    fn accept(&mut self, raw: Point) {
+        if let Some(&previous) = self.samples.back() {
+            self.step = previous.offset_to(raw);
+        }
+
+        self.samples.push_back(raw);
+        while self.samples.len() > self.config.smoothing_window.max(1) {
+            self.samples.pop_front();
+        }
+    }
+
+    fn on_touch(&mut self, position: Point) {
+        self.last_touch_frame = self.frame;
+        self.history.push_back((self.frame, position));
+        while self.history.len() > self.config.velocity_window.max(1) + 1 {
+            self.history.pop_front();
+        }
+
+        match self.state {
+            State::Idle => {
+                self.state = State::Pressed {
+                    origin: position,
+                    start_frame: self.frame,
+                    long_pressed: false,
+                };
+            }
+            State::Pressed {
+                origin,
+                start_frame,
+                long_pressed,
+            } => {
+                if origin.distance(position) > self.config.tap_slop {
+                    self.state = State::Dragging { origin };
+                    self.events.push(Gesture::DragStart {
+                        origin,
+                        region: self.hit_test(origin),
+                    });
+                    self.events.push(Gesture::Drag {
+                        position,
+                        delta: origin.offset_to(position),
+                        velocity: self.velocity(),
+                    });
+                } else if !long_pressed
+                    && self.frame - start_frame >= u64::from(self.config.long_press_frames)
+                {
+                    self.state = State::Pressed {
+                        origin,
+                        start_frame,
+                        long_pressed: true,
+                    };
+                    self.events.push(Gesture::LongPress {
+                        position: origin,
+                        region: self.hit_test(origin),
+                    });
+                }
+            }
+            State::Dragging { .. } => {
+                if position != self.position {
+                    self.events.push(Gesture::Drag {
+                        position,
+                        delta: self.position.offset_to(position),
+                        velocity: self.velocity(),
+                    });
+                }
+            }
+        }
+
+        self.position = position;
+    }
+
+    fn on_release(&mut self) {
+        match std::mem::replace(&mut self.state, State::Idle) {
+            State::Idle => {}
+            State::Pressed {
+                origin,
+                start_frame,
+                long_pressed,
+            } => {
+                let duration = self.last_touch_frame - start_frame + 1;
+
+                if !long_pressed && duration <= u64::from(self.config.tap_max_frames) {
+                    self.on_tap(origin);
+                }
+            }
+            State::Dragging { origin } => {
+                let velocity = self.velocity();
+                self.events.push(Gesture::DragEnd {
+                    position: self.position,
+                    region: self.hit_test(self.position),
+                    velocity,
+                });
+
+                if origin.distance(self.position) >= self.config.swipe_min_distance
+                    && velocity.magnitude() >= self.config.swipe_min_velocity
+                {
+                    let (dx, dy) = origin.offset_to(self.position);
+                    let direction = if dx.abs() >= dy.abs() {
+                        if dx < 0 {
+                            SwipeDirection::Left
+                        } else {
+                            SwipeDirection::Right
+                        }
+                    } else if dy < 0 {
+                        SwipeDirection::Up
+                    } else {
+                        SwipeDirection::Down
+                    };
+
+                    self.events.push(Gesture::Swipe {
+                        direction,
+                        velocity,
+                    });
+                }
+            }
+        }
+
+        self.samples.clear();
+        self.step = (0, 0);
+        self.pending = None;
+        self.history.clear();
+    }
+
+    fn on_tap(&mut self, position: Point) {
+        let region = self.hit_test(position);
+
+        match self.last_tap.take() {
+            Some((last_position, last_frame))
+                if self.frame - last_frame <= u64::from(self.config.double_tap_frames)
+                    && last_position.distance(position) <= self.config.double_tap_slop =>
+            {
+                self.events.push(Gesture::DoubleTap { position, region });
+            }
+            _ => {
+                self.events.push(Gesture::Tap { position, region });
+                self.last_tap = Some((position, self.frame));
+            }
+        }
+    }
+
+    fn velocity(&self) -> Velocity {
+        match (self.history.front(), self.history.back()) {
+            (Some(&(first_frame, first)), Some(&(last_frame, last)))
+                if last_frame > first_frame =>
+            {
+                let (dx, dy) = first.offset_to(last);
+                let frames = (last_frame - first_frame) as f32;
+
+                Velocity {
+                    x: dx as f32 / frames,
+                    y: dy as f32 / frames,
+                }
+            }
+            _ => Velocity::default(),
+        }
+    }
+}
+
+/// Returns how far `raw` is from `from` moved by `step`.
+fn deviation(from: Point, step: (i32, i32), raw: Point) -> f32 {
+    let (dx, dy) = from.offset_to(raw);
+    ((dx - step.0) as f32).hypot((dy - step.1) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds a whole trace and collects every gesture recognized along the way.
+    fn run(recognizer: &mut GestureRecognizer, trace: &[Option<(u16, u16)>]) -> Vec<Gesture> {
+        trace
+            .iter()
+            .flat_map(|sample| recognizer.update(*sample).to_vec())
+            .collect()
+    }
+
+    fn held(position: (u16, u16), frames: usize) -> Vec<Option<(u16, u16)>> {
+        vec![Some(position); frames]
+    }
+
+    fn released(frames: usize) -> Vec<Option<(u16, u16)>> {
+        vec![None; frames]
+    }
+
+    #[test]
+    fn tap_and_double_tap() {
+        let mut recognizer = GestureRecognizer::default();
+        let trace = [
+            held((100, 100), 5),
+            released(4),
+            held((102, 101), 4),
+            released(4),
+        ]
+        .concat();
+
+        let gestures = run(&mut recognizer, &trace);
+
+        assert_eq!(gestures.len(), 2);
+        assert!(
+            matches!(gestures[0], Gesture::Tap { position, .. } if position == Point::new(100, 100))
+        );
+        assert!(matches!(gestures[1], Gesture::DoubleTap { .. }));
+    }
+
+    #[test]
+    fn long_press_is_not_a_tap() {
+        let mut recognizer = GestureRecognizer::default();
+        let long_press_frames = recognizer.config().long_press_frames as usize;
+        let trace = [held((50, 60), long_press_frames + 10), released(3)].concat();
+
+        let gestures = run(&mut recognizer, &trace);
+
+        assert_eq!(
+            gestures,
+            [Gesture::LongPress {
+                position: Point::new(50, 60),
+                region: None
+            }]
+        );
+    }
+
+    #[test]
+    fn drag_and_swipe() {
+        let mut recognizer = GestureRecognizer::default();
+        let mut trace: Vec<_> = (0..20).map(|i| Some((40 + i * 10, 120))).collect();
+        trace.extend(released(3));
+
+        let gestures = run(&mut recognizer, &trace);
+
+        assert!(matches!(
+            gestures.first(),
+            Some(Gesture::DragStart { origin, .. }) if *origin == Point::new(40, 120)
+        ));
+        assert!(gestures
+            .iter()
+            .any(|g| matches!(g, Gesture::Drag { velocity, .. } if velocity.x > 9.0)));
+        assert!(matches!(
+            gestures[gestures.len() - 2],
+            Gesture::DragEnd { .. }
+        ));
+        assert!(matches!(
+            gestures.last(),
+            Some(Gesture::Swipe {
+                direction: SwipeDirection::Right,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn fast_flick_is_a_swipe() {
+        let mut recognizer = GestureRecognizer::default();
+        // Much faster than `max_jump` on every frame
+        let mut trace: Vec<_> = (0..6).map(|i| Some((20 + i * 55, 100 + i * 5))).collect();
+        trace.extend(released(3));
+
+        let gestures = run(&mut recognizer, &trace);
+
+        assert!(matches!(gestures.first(), Some(Gesture::DragStart { .. })));
+        assert!(matches!(
+            gestures.last(),
+            Some(Gesture::Swipe {
+                direction: SwipeDirection::Right,
+                velocity,
+            }) if velocity.x > 40.0
+        ));
+    }
+
+    #[test]
+    fn slow_drag_is_not_a_swipe() {
+        let mut recognizer = GestureRecognizer::default();
+        let mut trace: Vec<_> = (0..60).map(|i| Some((100, 200 - i))).collect();
+        trace.extend(held((100, 140), 10));
+        trace.extend(released(3));
+
+        let gestures = run(&mut recognizer, &trace);
+
+        assert!(matches!(gestures.last(), Some(Gesture::DragEnd { .. })));
+        assert!(!gestures.iter().any(|g| matches!(g, Gesture::Swipe { .. })));
+    }
+
+    #[test]
+    fn release_noise_is_ignored() {
+        let mut recognizer = GestureRecognizer::default();
+        // The panel flickers and reports (0, 0) while the stylus is being lifted.
+        let trace = [
+            held((200, 30), 3),
+            vec![None, Some((200, 31)), Some((0, 0)), None, Some((0, 0))],
+            released(3),
+        ]
+        .concat();
+
+        let gestures = run(&mut recognizer, &trace);
+
+        assert_eq!(gestures.len(), 1);
+        assert!(matches!(gestures[0], Gesture::Tap { .. }));
+    }
+
+    #[test]
+    fn single_spike_is_rejected() {
+        let mut recognizer = GestureRecognizer::default();
+        let trace = [
+            held((150, 150), 3),
+            vec![Some((10, 230))],
+            held((151, 150), 3),
+            released(3),
+        ]
+        .concat();
+
+        let gestures = run(&mut recognizer, &trace);
+
+        assert_eq!(gestures.len(), 1);
+        assert!(matches!(gestures[0], Gesture::Tap { .. }));
+    }
+
+    #[test]
+    fn regions_hit_test() {
+        let mut recognizer = GestureRecognizer::default();
+        let background = recognizer.add_region(Rect::new(0, 0, 320, 240));
+        let button = recognizer.add_region(Rect::new(10, 10, 50, 20));
+
+        assert_eq!(recognizer.hit_test(Point::new(20, 20)), Some(button));
+        assert_eq!(recognizer.hit_test(Point::new(60, 20)), Some(background));
+
+        let gestures = run(&mut recognizer, &[held((15, 25), 2), released(2)].concat());
+        assert_eq!(
+            gestures,
+            [Gesture::Tap {
+                position: Point::new(15, 25),
+                region: Some(button)
+            }]
+        );
+
+        assert!(recognizer.remove_region(button));
+        assert!(!recognizer.remove_region(button));
+        assert_eq!(recognizer.hit_test(Point::new(20, 20)), Some(background));
+    }
+}
@@ -4,7 +4,12 @@
 //! and circle pad information. It also provides information from the sound volume slider,
 //! the accelerometer, and the gyroscope.
 
+pub mod circle_pad;
+pub mod controls;
+pub mod gesture;
+
 use crate::error::ResultCode;
+
 bitflags::bitflags! {
     /// A set of flags corresponding to the button and directional pad
     /// inputs on the 3DS
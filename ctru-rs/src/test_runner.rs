@@ -26,6 +26,9 @@ pub(crate) fn run(tests: &[&TestDescAndFn]) {
     let opts = TestOpts {
         force_run_in_process: true,
         run_tests: true,
+        // `#[bench]` functions are run once like tests, unless the tests are built with the
+        // `CTRU_RS_BENCH` environment variable set, to measure them.
+        bench_benchmarks: option_env!("CTRU_RS_BENCH").is_some(),
         // TODO: color doesn't work because of TERM/TERMINFO.
         // With RomFS we might be able to fake this out nicely...
         color: ColorConfig::AutoColor,